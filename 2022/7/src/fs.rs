@@ -1,85 +1,153 @@
-use std::{collections::HashMap, cell::RefCell, rc::Rc};
+use std::collections::HashMap;
 
 use crate::common::{EntrySize, EntryName};
 
 #[derive(Debug)]
 pub enum Entry {
-    Directory(DirRef),
+    Directory(DirId),
     File {
         size: EntrySize
     },
 }
 
+/// Index of a directory inside its [`FileSystem`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DirId(usize);
+
 #[derive(Debug)]
 pub struct Directory {
+    pub name: EntryName,
+    pub parent: Option<DirId>,
     pub entries: HashMap<EntryName, Entry>,
     pub size: EntrySize,
 }
 
-#[derive(Debug)]
-pub struct DirRef(pub Rc<RefCell<Directory>>);
-
-impl Clone for DirRef {
-    fn clone(&self) -> Self {
-        Self(Rc::clone(&self.0))
+impl Directory {
+    fn new(name: EntryName, parent: Option<DirId>) -> Self {
+        Self {
+            name,
+            parent,
+            entries: HashMap::new(),
+            size: EntrySize::Calculated {
+                size: 0,
+                includes_indirect_sizes: false
+            }
+        }
     }
 }
 
-impl DirRef {
+/// Directory tree stored as an arena: directories refer to their parent and
+/// children by [`DirId`], so there are no reference cycles to leak. A
+/// directory is always created after its parent, which keeps parents before
+/// children in `dirs`.
+#[derive(Debug)]
+pub struct FileSystem {
+    dirs: Vec<Directory>,
+}
+
+impl FileSystem {
+    pub const ROOT: DirId = DirId(0);
+
     pub fn new() -> Self {
-        Self(
-            Rc::new(RefCell::new(
-                Directory {
-                    entries: HashMap::new(),
-                    size: EntrySize::Calculated {
-                        size: 0,
-                        includes_indirect_sizes: false
-                    }
-                }
-            ))
-        )
+        Self {
+            dirs: vec![Directory::new(EntryName::Root, None)]
+        }
+    }
+
+    pub fn get(&self, id: DirId) -> &Directory {
+        &self.dirs[id.0]
     }
 
-    pub fn new_subdir(&self, name: EntryName) {
-        if let EntryName::Regular(name) = name {
-            let new_subdir = DirRef::default();
+    /// Every directory reachable from the root, root first, each one after
+    /// its parent. A directory replaced by a file of the same name stays in
+    /// the arena but is no longer visited.
+    pub fn dirs(&self) -> impl Iterator<Item = (DirId, &Directory)> {
+        let mut pending = vec![Self::ROOT];
+
+        std::iter::from_fn(move || {
+            let id = pending.pop()?;
+            let dir = self.get(id);
+
+            pending.extend(dir.entries.values().filter_map(|entry| match entry {
+                Entry::Directory(subdir) => Some(*subdir),
+                Entry::File { .. } => None,
+            }));
 
-            new_subdir.0.borrow_mut().entries.insert(
-                EntryName::UpDir,
-                Entry::Directory(DirRef(Rc::clone(&self.0)))
-            );
+            Some((id, dir))
+        })
+    }
+
+    /// Absolute path of `id`, such as `/a/e`; the root is `/`.
+    pub fn path(&self, id: DirId) -> String {
+        let mut names = Vec::new();
+        let mut current = id;
 
-            self.0.borrow_mut().entries.insert(
-                EntryName::Regular(name),
-                Entry::Directory(DirRef(Rc::clone(&new_subdir.0)))
-            );
+        while let Some(parent) = self.get(current).parent {
+            names.push(self.get(current).name.as_str());
+            current = parent;
         }
 
-        else {
-            panic!("Cannot add root ( / ) to another directory")
+        if names.is_empty() {
+            return EntryName::Root.as_str().to_owned();
         }
+
+        names
+            .iter()
+            .rev()
+            .fold(String::new(), |path, name| path + "/" + name)
     }
 
-    pub fn new_file(&self, name: EntryName, size: EntrySize) {
-        if let EntryName::Regular(name) = name {
-            self.0.borrow_mut().entries.insert(
-                EntryName::Regular(name),
-                Entry::File { size }
-            );
+    /// Finds the directory at an absolute path such as `/a/e`. `..` steps up
+    /// to the parent, stopping at the root.
+    pub fn resolve(&self, path: &str) -> Option<DirId> {
+        let relative = path.strip_prefix('/')?;
+
+        relative
+            .split('/')
+            .filter(|component| !component.is_empty())
+            .try_fold(Self::ROOT, |dir, component| match component {
+                ".." => Some(self.get(dir).parent.unwrap_or(Self::ROOT)),
+                name => match self.get(dir).entries.get(&EntryName::Regular(name.to_owned()))? {
+                    Entry::Directory(subdir) => Some(*subdir),
+                    Entry::File { .. } => None,
+                },
+            })
+    }
+
+    pub fn new_subdir(&mut self, parent: DirId, name: EntryName) -> DirId {
+        if !matches!(name, EntryName::Regular(..)) {
+            panic!("Cannot add `{}` to another directory", name.as_str());
         }
 
-        else {
-            panic!("The name `/` cannot be attributed to a file");
+        if let Some(Entry::Directory(existing)) = self.dirs[parent.0].entries.get(&name) {
+            return *existing;
         }
+
+        let id = DirId(self.dirs.len());
+
+        self.dirs.push(Directory::new(name.clone(), Some(parent)));
+        self.dirs[parent.0].entries.insert(name, Entry::Directory(id));
+        self.invalidate_sizes(parent);
+
+        id
     }
 
-    pub fn get_subdir(&self, name: EntryName) -> DirRef {
+    pub fn new_file(&mut self, dir: DirId, name: EntryName, size: EntrySize) {
+        if !matches!(name, EntryName::Regular(..)) {
+            panic!("The name `{}` cannot be attributed to a file", name.as_str());
+        }
+
+        self.dirs[dir.0].entries.insert(name, Entry::File { size });
+        self.invalidate_sizes(dir);
+    }
 
+    pub fn get_subdir(&self, dir: DirId, name: EntryName) -> DirId {
         match name {
-            EntryName::Root => unreachable!("This case is already checked by the caller"),
-            _ => {
-                if let Some(Entry::Directory(dir)) = self.0.borrow().entries.get(&name) {
-                    dir.clone()
+            EntryName::Root => Self::ROOT,
+            EntryName::UpDir => self.get(dir).parent.unwrap_or(Self::ROOT),
+            EntryName::Regular(..) => {
+                if let Some(Entry::Directory(subdir)) = self.get(dir).entries.get(&name) {
+                    *subdir
                 }
                 else {
                     panic!("Directory `{}` does not exist!", name.as_str());
@@ -88,64 +156,52 @@ impl DirRef {
         }
     }
 
-    pub fn get_calculated_size(&self) -> u32 {
+    /// Size of `dir` including every file below it. Uses the cached size
+    /// when [`FileSystem::calculate_sizes`] has run since the last change.
+    pub fn get_calculated_size(&self, dir: DirId) -> u32 {
+        let directory = self.get(dir);
 
-        if let EntrySize::Calculated { size, includes_indirect_sizes } = self.0.borrow().size {
-            if includes_indirect_sizes {
-                return size;
-            }
+        if let EntrySize::Calculated { size, includes_indirect_sizes: true } = directory.size {
+            return size;
         }
 
-        let mut dir = self.0.borrow_mut();
+        directory.entries
+            .values()
+            .map(|entry| match entry {
+                Entry::Directory(subdir) => self.get_calculated_size(*subdir),
+                Entry::File { size } => size.get_size(),
+            })
+            .sum()
+    }
 
-        let mut size = 0;
+    /// Caches the size of every directory, children before their parents.
+    pub fn calculate_sizes(&mut self) {
+        for idx in (0..self.dirs.len()).rev() {
+            let size = self.get_calculated_size(DirId(idx));
 
-        for (name, entry) in dir.entries.iter() {
-            match entry {
-                Entry::Directory(subdir) => {
-                    if matches!(name, EntryName::Regular( .. )) {
-                        let subdir = DirRef(Rc::clone(&subdir.0));
-                        size += subdir.get_calculated_size();
-                    }
-                },
-                Entry::File { size: file_size } => {
-                    size += file_size.get_size();
-                }
-            }
+            self.dirs[idx].size = EntrySize::Calculated {
+                size,
+                includes_indirect_sizes: true
+            };
         }
-
-        dir.size = EntrySize::Calculated {
-            size,
-            includes_indirect_sizes: true
-        };
-
-        size
     }
 
-    pub fn dump(&self) {
-        for (name, entry) in self.0.borrow().entries.iter() {
-            match entry {
-                Entry::Directory(dir) => {
-                    match name {
-                        EntryName::Root => unreachable!("Root cannot be a subdir"),
-                        EntryName::UpDir => println!("dir .. enddir"),
-                        EntryName::Regular(name) => {
-                            println!("dir {name} {:?}", dir.get_calculated_size());
-                            dir.dump();
-                            println!("enddir {name}");
-                        }
-                    }
-                },
-                Entry::File { size } => {
-                    println!("File: {name:?} {size:?}");
-                }
-            }
+    fn invalidate_sizes(&mut self, dir: DirId) {
+        let mut current = Some(dir);
+
+        while let Some(id) = current {
+            let directory = &mut self.dirs[id.0];
+            directory.size = EntrySize::Calculated {
+                size: 0,
+                includes_indirect_sizes: false
+            };
+            current = directory.parent;
         }
     }
 }
 
-impl Default for DirRef {
+impl Default for FileSystem {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
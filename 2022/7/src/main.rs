@@ -1,11 +1,8 @@
-#![feature(let_chains)]
-
 mod fs;
 mod common;
 mod parsing;
 
-use common::EntryName;
-use fs::{Entry, DirRef};
+use fs::FileSystem;
 use parsing::{LineType, FileListingType, CommandType};
 
 const DIRECTORY_SIZE_LIMIT_TO_FIND: u32 = 100_000;
@@ -16,18 +13,18 @@ fn main() {
 
     let input: Vec<&str> = include_str!("../input.txt").lines().collect();
 
-    let root = DirRef::new();
+    let mut fs = FileSystem::new();
+
+    populate_entries(input, &mut fs);
 
-    populate_entries(input, &root);
+    fs.calculate_sizes();
 
-    let total_used_space = root.get_calculated_size(); // Forces directory and subdirectory size calculation
+    let total_used_space = fs.get_calculated_size(FileSystem::ROOT);
     let available_space = TOTAL_DISK_SPACE - total_used_space;
 
     let space_to_free = REQUIRED_DISK_SPACE - available_space;
 
-    // root.dump();
-
-    let dirs_up_to = find_dir_sizes_up_to(&root, DIRECTORY_SIZE_LIMIT_TO_FIND);
+    let dirs_up_to = find_dir_sizes_up_to(&fs, DIRECTORY_SIZE_LIMIT_TO_FIND);
 
     // dbg!(&dirs_up_to);
 
@@ -36,117 +33,216 @@ fn main() {
         dirs_up_to.iter().map(|(_, size)| size).sum::<u32>()
     ); // Part 1
 
-    let dir_to_delete = find_minimum_size_dir(&root, space_to_free);
+    let dir_to_delete = find_minimum_size_dir(&fs, space_to_free);
 
-    if let Some(dir) = dir_to_delete {
-        println!(
-            "Size of the directory to delete: {:?}",
-            dir.get_calculated_size()
-        );
+    if let Some((path, size)) = dir_to_delete {
+        println!("Size of the directory to delete: {size} ({path})");
     }
     else {
         println!("No directory found to delete!");
     } // Part 2
 
+    for path in std::env::args().skip(1) {
+        match fs.resolve(&path) {
+            Some(dir) => println!("Size of {}: {}", fs.path(dir), fs.get_calculated_size(dir)),
+            None => println!("No directory at {path}"),
+        }
+    }
+
 }
 
-fn populate_entries(input: Vec<&str>, root: &DirRef) {
+fn populate_entries(input: Vec<&str>, fs: &mut FileSystem) {
 
-    let mut visitor = root.clone();
+    let mut visitor = FileSystem::ROOT;
 
     for line in input {
-        let line_type: LineType = line.parse().unwrap();
+        let line_type: LineType = line
+            .parse()
+            .unwrap_or_else(|error| panic!("Invalid line `{line}`: {error}"));
 
         match line_type {
             LineType::Command(command) => {
                 if let CommandType::Cd(directory_name) = command {
-                    match directory_name {
-                        EntryName::Root => visitor = root.clone(),
-                        EntryName::Regular(..) | EntryName::UpDir => visitor = visitor.get_subdir(directory_name),
-                    }
+                    visitor = fs.get_subdir(visitor, directory_name);
                 }
             },
             LineType::FileListing(file_listing) => match file_listing {
-                FileListingType::File(size, name) => visitor.new_file(name, size),
-                FileListingType::Directory(name) => visitor.new_subdir(name),
+                FileListingType::File(size, name) => fs.new_file(visitor, name, size),
+                FileListingType::Directory(name) => {
+                    fs.new_subdir(visitor, name);
+                },
             }
         };
 
     }
 }
 
-fn find_dir_sizes_up_to(root: &DirRef, limit: u32) -> Vec<(String, u32)> {
+/// Every directory below the root whose size is at most `limit`, as
+/// `(path, size)` pairs.
+fn find_dir_sizes_up_to(fs: &FileSystem, limit: u32) -> Vec<(String, u32)> {
+    fs.dirs()
+        .filter(|(id, _)| *id != FileSystem::ROOT)
+        .map(|(id, _)| (fs.path(id), fs.get_calculated_size(id)))
+        .filter(|(_, size)| *size <= limit)
+        .collect()
+}
+
+/// The smallest directory of at least `size_required`, as `(path, size)`.
+fn find_minimum_size_dir(fs: &FileSystem, size_required: u32) -> Option<(String, u32)> {
+    fs.dirs()
+        .map(|(id, _)| (id, fs.get_calculated_size(id)))
+        .filter(|(_, size)| *size >= size_required)
+        .min_by_key(|(_, size)| *size)
+        .map(|(id, size)| (fs.path(id), size))
+}
 
-    let mut dirs_and_sizes = Vec::new();
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::{EntryName, EntrySize},
+        fs::FileSystem,
+        populate_entries, find_dir_sizes_up_to, find_minimum_size_dir,
+        DIRECTORY_SIZE_LIMIT_TO_FIND, TOTAL_DISK_SPACE, REQUIRED_DISK_SPACE,
+    };
 
-    let dir = root.0.borrow();
+    fn example_fs() -> FileSystem {
+        let input: Vec<&str> = include_str!("../test_input.txt").lines().collect();
 
-    for (name, entry) in dir.entries.iter() {
-        if let Entry::Directory(subdir) = entry &&
-            let EntryName::Regular(name) = name
-        {
-            if subdir.get_calculated_size() <= limit {
-                dirs_and_sizes.push((name.clone(), subdir.get_calculated_size()));
-            }
-            dirs_and_sizes.extend(find_dir_sizes_up_to(subdir, limit))
-        }
+        let mut fs = FileSystem::new();
+        populate_entries(input, &mut fs);
+        fs.calculate_sizes();
+
+        fs
     }
 
-    dirs_and_sizes
+    fn regular(name: &str) -> EntryName {
+        EntryName::Regular(name.to_owned())
+    }
 
-}
+    #[test]
+    fn example_part_1() {
+        let fs = example_fs();
 
-fn find_minimum_size_dir(root: &DirRef, size_required: u32) -> Option<DirRef> {
+        let dirs_up_to = find_dir_sizes_up_to(&fs, DIRECTORY_SIZE_LIMIT_TO_FIND);
 
-    let dir = root.0.borrow();
+        assert_eq!(dirs_up_to.iter().map(|(_, size)| size).sum::<u32>(), 95437);
+    }
 
-    let mut current_minimum = if root.get_calculated_size() >= size_required {
-        Some(root.clone())
-    } else {
-        None
-    };
+    #[test]
+    fn example_part_2() {
+        let fs = example_fs();
 
-    for (name, entry) in dir.entries.iter() {
-        if let Entry::Directory(subdir) = entry &&
-            matches!(name, EntryName::Regular(..))
-        {
-            let mut candidate = if subdir.get_calculated_size() >= size_required {
-                Some(subdir.clone())
-            }
-            else {
-                None
-            };
-
-            let subdir_candidate = find_minimum_size_dir(subdir, size_required);
-
-            candidate = match (&candidate, &subdir_candidate) {
-                (Some(candidate_dir), Some(subdir_candidate_dir)) => {
-                    if candidate_dir.get_calculated_size() < subdir_candidate_dir.get_calculated_size() {
-                        candidate
-                    } else {
-                        subdir_candidate
-                    }
-                },
-                (Some(..), None) => candidate,
-                (None, Some(..)) => subdir_candidate,
-                (None, None) => None,
-            };
-
-            current_minimum = match (&current_minimum, &candidate) {
-                (Some(curr_dir), Some(candidate_dir)) => {
-                    if curr_dir.get_calculated_size() < candidate_dir.get_calculated_size() {
-                        current_minimum
-                    } else {
-                        candidate
-                    }
-                },
-                (Some(..), None) => current_minimum,
-                (None, Some(..)) => candidate,
-                (None, None) => None,
-            }
+        let available_space = TOTAL_DISK_SPACE - fs.get_calculated_size(FileSystem::ROOT);
+        let space_to_free = REQUIRED_DISK_SPACE - available_space;
+
+        let dir_to_delete = find_minimum_size_dir(&fs, space_to_free);
+
+        assert_eq!(dir_to_delete.map(|(_, size)| size), Some(24933642));
+    }
+
+    #[test]
+    fn up_dir_returns_to_the_parent() {
+        let fs = example_fs();
+
+        let a = fs.get_subdir(FileSystem::ROOT, regular("a"));
+        let e = fs.get_subdir(a, regular("e"));
+
+        assert_eq!(fs.get_subdir(e, EntryName::UpDir), a);
+        assert_eq!(fs.get_subdir(a, EntryName::UpDir), FileSystem::ROOT);
+        assert_eq!(fs.get_subdir(FileSystem::ROOT, EntryName::UpDir), FileSystem::ROOT);
+        assert_eq!(fs.get_subdir(e, EntryName::Root), FileSystem::ROOT);
+    }
+
+    #[test]
+    fn creating_a_directory_twice_keeps_it() {
+        let mut fs = example_fs();
+
+        let a = fs.get_subdir(FileSystem::ROOT, regular("a"));
+        let dirs = fs.dirs().count();
+
+        assert_eq!(fs.new_subdir(FileSystem::ROOT, regular("a")), a);
+        assert_eq!(fs.dirs().count(), dirs);
+
+        fs.new_file(a, regular("extra"), EntrySize::Intrisic(6));
+        assert_eq!(fs.get_calculated_size(a), 94853 + 6);
+        assert_eq!(fs.get_calculated_size(FileSystem::ROOT), 48381165 + 6);
+    }
+
+    #[test]
+    fn resolve_paths() {
+        let fs = example_fs();
+
+        let a = fs.get_subdir(FileSystem::ROOT, regular("a"));
+        let e = fs.get_subdir(a, regular("e"));
+
+        assert_eq!(fs.resolve("/a/e"), Some(e));
+        assert_eq!(fs.resolve("/a/e/"), Some(e));
+        assert_eq!(fs.resolve("/"), Some(FileSystem::ROOT));
+        assert_eq!(fs.resolve("/.."), Some(FileSystem::ROOT));
+        assert_eq!(fs.resolve("/../a"), Some(a));
+        assert_eq!(fs.resolve("/a/e/../.."), Some(FileSystem::ROOT));
+
+        assert_eq!(fs.resolve("a/e"), None);
+        assert_eq!(fs.resolve(""), None);
+        assert_eq!(fs.resolve("/b.txt"), None);
+        assert_eq!(fs.resolve("/a/f/e"), None);
+        assert_eq!(fs.resolve("/a/x"), None);
+    }
+
+    #[test]
+    fn paths_round_trip() {
+        let fs = example_fs();
+
+        assert_eq!(fs.path(FileSystem::ROOT), "/");
+
+        for path in ["/", "/a", "/a/e", "/d"] {
+            assert_eq!(fs.resolve(path).map(|dir| fs.path(dir)).as_deref(), Some(path));
+        }
+
+        for (id, _) in fs.dirs() {
+            assert_eq!(fs.resolve(&fs.path(id)), Some(id));
         }
     }
 
-    current_minimum
+    #[test]
+    fn same_named_directories_have_distinct_paths() {
+        let mut fs = example_fs();
+
+        let d = fs.get_subdir(FileSystem::ROOT, regular("d"));
+        let d_e = fs.new_subdir(d, regular("e"));
+        fs.new_file(d_e, regular("k"), EntrySize::Intrisic(7));
+        fs.calculate_sizes();
+
+        let a_e = fs.resolve("/a/e").unwrap();
+        assert_ne!(a_e, d_e);
+        assert_eq!(fs.path(a_e), "/a/e");
+        assert_eq!(fs.path(d_e), "/d/e");
 
-}
\ No newline at end of file
+        let mut dirs_up_to = find_dir_sizes_up_to(&fs, DIRECTORY_SIZE_LIMIT_TO_FIND);
+        dirs_up_to.sort();
+
+        assert_eq!(dirs_up_to, [
+            ("/a".to_owned(), 94853),
+            ("/a/e".to_owned(), 584),
+            ("/d/e".to_owned(), 7),
+        ]);
+
+        assert_eq!(find_minimum_size_dir(&fs, 500), Some(("/a/e".to_owned(), 584)));
+    }
+
+    #[test]
+    fn file_replacing_a_directory_hides_it() {
+        let mut fs = example_fs();
+
+        let a = fs.get_subdir(FileSystem::ROOT, regular("a"));
+        fs.new_file(a, regular("e"), EntrySize::Intrisic(10));
+        fs.calculate_sizes();
+
+        assert_eq!(fs.dirs().count(), 3);
+        assert!(fs.dirs().all(|(_, dir)| dir.name != regular("e")));
+        assert_eq!(fs.get_calculated_size(a), 94853 - 584 + 10);
+
+        let dirs_up_to = find_dir_sizes_up_to(&fs, DIRECTORY_SIZE_LIMIT_TO_FIND);
+        assert_eq!(dirs_up_to.len(), 1);
+    }
+}
@@ -1,6 +1,6 @@
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 
-use crate::{common::{EntryName, EntrySize}, fs::{Entry, DirRef}};
+use crate::common::{EntryName, EntrySize};
 
 #[derive(Debug)]
 pub enum LineType {
@@ -91,21 +91,6 @@ impl FromStr for FileListingType {
     }
 }
 
-impl From<FileListingType> for (EntryName, Entry) {
-    fn from(value: FileListingType) -> Self {
-        match value {
-            FileListingType::File(size, name) => (name, Entry::File { size }),
-            FileListingType::Directory(name) => {
-                let entry = Entry::Directory(
-                    DirRef::new()
-                );
-
-                (name, entry)
-            },
-        }
-    }
-}
-
 #[derive(Debug, Clone)]
 pub enum CommandParseError {
     SplitError,
@@ -125,4 +110,40 @@ pub enum LineTypeParseError {
         Option<CommandParseError>,
         Option<FileListingParseError>,
     )
-}
\ No newline at end of file
+}
+
+impl fmt::Display for CommandParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SplitError => write!(f, "command has no argument"),
+            Self::UnknownCommand(command) => write!(f, "unknown command `{command}`"),
+            Self::NotACommandError => write!(f, "not a command"),
+        }
+    }
+}
+
+impl fmt::Display for FileListingParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SplitError => write!(f, "listing has no name"),
+            Self::UnknownFileListing(listing) => write!(f, "unknown listing `{listing}`"),
+        }
+    }
+}
+
+impl fmt::Display for LineTypeParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NeitherCommandNorLine(command_error, file_listing_error) => {
+                write!(f, "neither a command nor a listing")?;
+                if let Some(error) = command_error {
+                    write!(f, "; as a command: {error}")?;
+                }
+                if let Some(error) = file_listing_error {
+                    write!(f, "; as a listing: {error}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
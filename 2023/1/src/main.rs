@@ -1,85 +1,258 @@
-#![feature(custom_test_frameworks)]
+#![cfg_attr(test, feature(test))]
 
-fn main() {
+use std::{fmt, str::FromStr};
+
+use scanner::{DigitMatch, DigitScanner};
+
+mod scanner;
+
+fn main() -> Result<(), NoDigits> {
     const INPUT: &str = include_str!("../input.txt");
-    let result = calibration_values(INPUT);
+    let args: Vec<String> = std::env::args().collect();
+    let explain = args.iter().any(|arg| arg == "--explain");
+    let policy = match args.iter().skip_while(|arg| *arg != "--no-digits").nth(1) {
+        Some(policy) => policy.parse().unwrap_or_else(|policy| {
+            eprintln!("Invalid --no-digits `{policy}`, expected error, skip or zero");
+            std::process::exit(2);
+        }),
+        None => NoDigitsPolicy::Error,
+    };
+    let custom_words = args.iter().skip_while(|arg| *arg != "--words").nth(1).map(|words| {
+        parse_words(words).unwrap_or_else(|| {
+            eprintln!("Invalid --words `{words}`, expected word=value pairs such as um=1,dois=2");
+            std::process::exit(2);
+        })
+    });
+
+    let mut vocabularies = vec![("Part 1", Vocabulary::Digits), ("Part 2", Vocabulary::English)];
+    if let Some(words) = &custom_words {
+        vocabularies.push(("Custom", Vocabulary::Custom(words)));
+    }
 
-    println!("Result: {result}");
+    for (label, vocabulary) in vocabularies {
+        if explain {
+            let calibration = calibration_report(INPUT, &vocabulary, NoDigitsPolicy::Skip)?;
+            for line in &calibration.lines {
+                println!("{line}");
+            }
+            for line in &calibration.skipped {
+                println!("line {line}: skipped, no digits");
+            }
+        }
+
+        let result = calibration_values(INPUT, &vocabulary, policy)?;
+        println!("{label}: {result}");
+    }
+
+    Ok(())
 }
 
-fn calibration_values(input: &str) -> u64 {
-    input
-        .lines()
-        .filter(|line| !line.is_empty())
-        .map(|line| {            
-            let (first_digit, last_digit) = fetch_first_and_last_digits(line);
-            (first_digit * 10 + last_digit) as u64
-        })
-        .sum()
+/// What to do with a line where no digit can be found, picked with
+/// `--no-digits` on the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NoDigitsPolicy {
+    /// Fail on the first such line.
+    Error,
+    /// Leave the line out of the total and list it in [`Calibration::skipped`].
+    Skip,
+    /// Count the line as `0`.
+    Zero,
 }
 
-fn fetch_first_and_last_digits(line: &str) -> (u8, u8) {
-    let (first_digit, _) = fetch_digit(line, DigitIdxSorting::First).unwrap_or_default();
+impl FromStr for NoDigitsPolicy {
+    type Err = String;
 
-    let last_digit = match fetch_digit(line, DigitIdxSorting::Last) {
-        Some((last_digit, _)) => last_digit,
-        None => first_digit,
-    };
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "error" => Ok(Self::Error),
+            "skip" => Ok(Self::Skip),
+            "zero" => Ok(Self::Zero),
+            _ => Err(s.to_owned()),
+        }
+    }
+}
 
-    (first_digit, last_digit)
-}
-
-#[repr(i8)]
-#[derive(Clone, Copy)]
-enum DigitIdxSorting {
-    First = -1,
-    Last = 1,
-}
-
-fn fetch_digit(line: &str, sorting: DigitIdxSorting) -> Option<(u8, usize)> {
-    static DIGIT_NAMES_AND_VALUES: &[(&str, u8)] = &[
-        ("zero", 0),
-        ("one", 1),
-        ("two", 2),
-        ("three", 3),
-        ("four", 4),
-        ("five", 5),
-        ("six", 6),
-        ("seven", 7),
-        ("eight", 8),
-        ("nine", 9),
-        ("0", 0),
-        ("1", 1),
-        ("2", 2),
-        ("3", 3),
-        ("4", 4),
-        ("5", 5),
-        ("6", 6),
-        ("7", 7),
-        ("8", 8),
-        ("9", 9),
-    ];
+/// Number of a line in the input, 1-based and counting blank lines too.
+type LineNumber = usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct NoDigits {
+    line: LineNumber,
+}
+
+impl fmt::Display for NoDigits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no digits on line {}", self.line)
+    }
+}
+
+#[derive(Debug)]
+struct Calibration<'a> {
+    total: u64,
+    /// Every line that made it into the total.
+    lines: Vec<LineBreakdown<'a>>,
+    /// Numbers of the lines left out under [`NoDigitsPolicy::Skip`].
+    skipped: Vec<LineNumber>,
+}
 
-    if let Some((at, digit)) = DIGIT_NAMES_AND_VALUES
-        .iter()
-        .filter_map(|(name, value)| {
-            match sorting {
-                DigitIdxSorting::First => line.find(name),
-                DigitIdxSorting::Last => line.rfind(name),
-            }.map(|at| (at, value))
+/// The digits picked from a single line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LineBreakdown<'a> {
+    line: LineNumber,
+    text: &'a str,
+    /// First and last match, which are the same one if the line only has one.
+    digits: Option<(DigitMatch, DigitMatch)>,
+}
+
+impl LineBreakdown<'_> {
+    fn value(&self) -> u64 {
+        self.digits
+            .map(|(first, last)| u64::from(first.digit) * 10 + u64::from(last.digit))
+            .unwrap_or_default()
+    }
+}
+
+impl fmt::Display for LineBreakdown<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {} -> ", self.line, self.text)?;
+
+        match self.digits {
+            Some((first, last)) => write!(
+                f,
+                "first {:?} at {}, last {:?} at {} = {}",
+                &self.text[first.start..first.end], first.start,
+                &self.text[last.start..last.end], last.start,
+                self.value(),
+            ),
+            None => write!(f, "no digits = 0"),
+        }
+    }
+}
+
+fn calibration_values(input: &str, vocabulary: &Vocabulary, policy: NoDigitsPolicy) -> Result<u64, NoDigits> {
+    calibration_report(input, vocabulary, policy).map(|calibration| calibration.total)
+}
+
+/// Blank lines are ignored; every other line goes through `policy` if no digit
+/// can be found in it.
+fn calibration_report<'a>(
+    input: &'a str,
+    vocabulary: &Vocabulary,
+    policy: NoDigitsPolicy,
+) -> Result<Calibration<'a>, NoDigits> {
+    let scanner = vocabulary.scanner();
+    let mut lines = Vec::new();
+    let mut skipped = Vec::new();
+
+    for (idx, text) in input.lines().enumerate() {
+        let text = text.trim();
+        if text.is_empty() {
+            continue;
+        }
+
+        let breakdown = LineBreakdown {
+            line: idx + 1,
+            text,
+            digits: fetch_first_and_last_digits(&scanner, text),
+        };
+
+        match (breakdown.digits, policy) {
+            (None, NoDigitsPolicy::Error) => return Err(NoDigits { line: breakdown.line }),
+            (None, NoDigitsPolicy::Skip) => skipped.push(breakdown.line),
+            _ => lines.push(breakdown),
+        }
+    }
+
+    Ok(Calibration {
+        total: lines.iter().map(LineBreakdown::value).sum(),
+        lines,
+        skipped,
+    })
+}
+
+fn fetch_first_and_last_digits(scanner: &DigitScanner, line: &str) -> Option<(DigitMatch, DigitMatch)> {
+    let mut matches = scanner.find_iter(line);
+    let found = matches.next()?;
+
+    // Matches come ordered by where they end, which isn't always where they
+    // start once words can nest inside others, so track both ends explicitly.
+    Some(matches.fold((found, found), |(first, last), found| (
+        if found.start < first.start { found } else { first },
+        if found.start >= last.start { found } else { last },
+    )))
+}
+
+/// Which words count as digits. Numerals always do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Vocabulary<'a> {
+    /// Part 1: numerals only.
+    Digits,
+    /// Part 2: numerals and `one` through `nine`.
+    English,
+    /// Numerals and the given words, e.g. digit names in another language,
+    /// as passed with `--words`.
+    /// Values above 9 are taken as-is, so a line's value is still
+    /// `first * 10 + last`.
+    Custom(&'a [(&'a str, u8)]),
+}
+
+/// Reads comma separated `word=value` pairs, such as `um=1,dois=2`.
+fn parse_words(arg: &str) -> Option<Vec<(&str, u8)>> {
+    arg.split(',')
+        .map(|pair| {
+            let (word, value) = pair.split_once('=')?;
+            let value = value.parse().ok()?;
+            (!word.is_empty()).then_some((word, value))
         })
-        .max_by_key(|(at, _)| (*at as i64) * (sorting as i64))
-    {
-            return Some((*digit, at));
+        .collect()
+}
+
+static NUMERALS: &[(&str, u8)] = &[
+    ("0", 0),
+    ("1", 1),
+    ("2", 2),
+    ("3", 3),
+    ("4", 4),
+    ("5", 5),
+    ("6", 6),
+    ("7", 7),
+    ("8", 8),
+    ("9", 9),
+];
+
+static ENGLISH_DIGIT_NAMES: &[(&str, u8)] = &[
+    ("one", 1),
+    ("two", 2),
+    ("three", 3),
+    ("four", 4),
+    ("five", 5),
+    ("six", 6),
+    ("seven", 7),
+    ("eight", 8),
+    ("nine", 9),
+];
+
+impl Vocabulary<'_> {
+    fn words(&self) -> Vec<(&str, u8)> {
+        let names = match self {
+            Vocabulary::Digits => &[],
+            Vocabulary::English => ENGLISH_DIGIT_NAMES,
+            Vocabulary::Custom(names) => *names,
+        };
+
+        NUMERALS.iter().chain(names).copied().collect()
     }
 
-    None
+    fn scanner(&self) -> DigitScanner {
+        DigitScanner::new(&self.words())
+    }
 }
 
 #[cfg(test)]
 mod test {
     use test_case::test_case;
-    use crate::{calibration_values, fetch_digit, DigitIdxSorting, fetch_first_and_last_digits};
+    use crate::{calibration_values, calibration_report, fetch_first_and_last_digits, Vocabulary, NoDigits, NoDigitsPolicy, ENGLISH_DIGIT_NAMES, NUMERALS, parse_words};
+    use crate::scanner::{DigitMatch, DigitScanner};
 
     #[test]
     fn base_case() {
@@ -92,9 +265,9 @@ mod test {
 
         let expected_output = 142;
 
-        let output = calibration_values(example_input);
+        let output = calibration_values(example_input, &Vocabulary::Digits, NoDigitsPolicy::Error);
 
-        assert_eq!(output, expected_output);
+        assert_eq!(output, Ok(expected_output));
     }
 
     #[test_case("1", Some((1, 0)) ; "single digit")]
@@ -102,11 +275,13 @@ mod test {
     #[test_case("31", Some((3, 0)) ; "multiple digits")]
     #[test_case("fourtwo", Some((4, 0)) ; "multiple named digits")]
     #[test_case("2six", Some((2, 0)) ; "mixed digit first")]
-    #[test_case("zero8", Some((0, 0)) ; "mixed named digit first")]
+    #[test_case("seven8", Some((7, 0)) ; "mixed named digit first")]
+    #[test_case("zero8", Some((8, 4)) ; "zero is not a named digit")]
     #[test_case("alotoftextfivemoretext", Some((5, 10)) ; "trash surrounding named digit")]
     #[test_case("nothing here!", None ; "no digits")]
     fn test_fetch_first_digit(input_line: &str, expected_output: Option<(u8, usize)>) {
-        let output = fetch_digit(input_line, DigitIdxSorting::First);
+        let output = fetch_first_and_last_digits(&Vocabulary::English.scanner(), input_line)
+            .map(|(first, _)| (first.digit, first.start));
         assert_eq!(output, expected_output);
     }
 
@@ -119,7 +294,8 @@ mod test {
     #[test_case("alotoftextfivemoretext", Some((5, 10)) ; "trash surrounding named digit")]
     #[test_case("nothing here!", None ; "no digits")]
     fn test_fetch_last_digit(input_line: &str, expected_output: Option<(u8, usize)>) {
-        let output = fetch_digit(input_line, DigitIdxSorting::Last);
+        let output = fetch_first_and_last_digits(&Vocabulary::English.scanner(), input_line)
+            .map(|(_, last)| (last.digit, last.start));
         assert_eq!(output, expected_output);
     }
 
@@ -131,9 +307,9 @@ mod test {
     #[test_case("427nine6chnqrssxfour", 4, 4)]
     #[test_case("threenine3five9eightrvg9", 3, 9)]
     fn test_fetch_first_and_last(input_line: &str, expected_first: u8, expected_last: u8) {
-        let (first, last) = fetch_first_and_last_digits(input_line);
-        assert_eq!(first, expected_first);
-        assert_eq!(last, expected_last);
+        let (first, last) = fetch_first_and_last_digits(&Vocabulary::English.scanner(), input_line).unwrap();
+        assert_eq!(first.digit, expected_first);
+        assert_eq!(last.digit, expected_last);
     }
 
     #[test]
@@ -150,8 +326,181 @@ mod test {
 
         let expected_output = 281;
 
-        let output = calibration_values(example_input);
+        let output = calibration_values(example_input, &Vocabulary::English, NoDigitsPolicy::Error);
 
-        assert_eq!(output, expected_output)
+        assert_eq!(output, Ok(expected_output))
     }
-}
\ No newline at end of file
+
+    #[test_case("eightwo", &[(8, 0), (2, 4)] ; "overlapping words")]
+    #[test_case("oneight7twone", &[(1, 0), (8, 2), (7, 7), (2, 8), (1, 10)] ; "chained overlaps")]
+    #[test_case("sevenine", &[(7, 0), (9, 4)] ; "shared letter")]
+    #[test_case("ttwo", &[(2, 1)] ; "restart after a false start")]
+    #[test_case("nothing here!", &[] ; "no digits")]
+    fn test_scanner_finds_every_match(input_line: &str, expected: &[(u8, usize)]) {
+        let scanner = Vocabulary::English.scanner();
+        let found: Vec<(u8, usize)> = scanner
+            .find_iter(input_line)
+            .map(|found| (found.digit, found.start))
+            .collect();
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn test_scanner_reports_nested_words() {
+        let scanner = DigitScanner::new(&[("abcd", 1), ("bc", 2), ("c", 3)]);
+        let found: Vec<DigitMatch> = scanner.find_iter("xabcd").collect();
+        assert_eq!(found, vec![
+            DigitMatch { digit: 2, start: 2, end: 4 },
+            DigitMatch { digit: 3, start: 3, end: 4 },
+            DigitMatch { digit: 1, start: 1, end: 5 },
+        ]);
+    }
+
+    #[test_case("two1nine", Vocabulary::Digits, Some((1, 1)) ; "digits ignore words")]
+    #[test_case("two1nine", Vocabulary::English, Some((2, 9)) ; "english words")]
+    #[test_case("zero", Vocabulary::English, None ; "no words for zero")]
+    #[test_case("0ab", Vocabulary::Digits, Some((0, 0)) ; "zero numeral")]
+    #[test_case("xdoisum7", Vocabulary::Custom(PORTUGUESE), Some((2, 7)) ; "custom words")]
+    #[test_case("trêsoito", Vocabulary::Custom(PORTUGUESE), Some((3, 8)) ; "non ascii words")]
+    #[test_case("seisete", Vocabulary::Custom(PORTUGUESE), Some((6, 7)) ; "custom overlapping words")]
+    #[test_case("one", Vocabulary::Custom(PORTUGUESE), None ; "custom replaces english")]
+    fn test_vocabularies(input_line: &str, vocabulary: Vocabulary, expected: Option<(u8, u8)>) {
+        let output = fetch_first_and_last_digits(&vocabulary.scanner(), input_line)
+            .map(|(first, last)| (first.digit, last.digit));
+        assert_eq!(output, expected);
+    }
+
+    #[test_case("um=1,dois=2", Some(vec![("um", 1), ("dois", 2)]))]
+    #[test_case("ten=10", Some(vec![("ten", 10)]))]
+    #[test_case("um=1,", None)]
+    #[test_case("=1", None)]
+    #[test_case("um", None)]
+    #[test_case("um=256", None)]
+    fn test_parse_words(input: &str, expected: Option<Vec<(&str, u8)>>) {
+        assert_eq!(parse_words(input), expected);
+    }
+
+    #[test]
+    fn test_custom_values_above_nine() {
+        let words: &[(&str, u8)] = &[("ten", 10), ("max", u8::MAX)];
+        let output = calibration_values("ten\nmax1\nmaxmax", &Vocabulary::Custom(words), NoDigitsPolicy::Error);
+        assert_eq!(output, Ok(110 + (255 * 10 + 1) + 255 * 11));
+    }
+
+    #[test_case("error", Ok(NoDigitsPolicy::Error))]
+    #[test_case("skip", Ok(NoDigitsPolicy::Skip))]
+    #[test_case("zero", Ok(NoDigitsPolicy::Zero))]
+    #[test_case("Zero", Err("Zero".to_owned()))]
+    fn test_parse_no_digits_policy(input: &str, expected: Result<NoDigitsPolicy, String>) {
+        assert_eq!(input.parse(), expected);
+    }
+
+    const INPUT_WITH_GAPS: &str = "1abc2\nnothing\n\ntreb7uchet\nno digits here";
+
+    #[test_case(NoDigitsPolicy::Error, Err(NoDigits { line: 2 }))]
+    #[test_case(NoDigitsPolicy::Skip, Ok(12 + 77))]
+    #[test_case(NoDigitsPolicy::Zero, Ok(12 + 77))]
+    fn test_no_digits_policies(policy: NoDigitsPolicy, expected: Result<u64, NoDigits>) {
+        let output = calibration_values(INPUT_WITH_GAPS, &Vocabulary::Digits, policy);
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_skipped_lines_are_reported() {
+        let skip = calibration_report(INPUT_WITH_GAPS, &Vocabulary::Digits, NoDigitsPolicy::Skip).unwrap();
+        assert_eq!(skip.skipped, vec![2, 5]);
+        assert_eq!(skip.lines.iter().map(|line| line.line).collect::<Vec<_>>(), vec![1, 4]);
+
+        let zero = calibration_report(INPUT_WITH_GAPS, &Vocabulary::Digits, NoDigitsPolicy::Zero).unwrap();
+        assert!(zero.skipped.is_empty());
+        assert_eq!(zero.lines.iter().map(|line| line.value()).collect::<Vec<_>>(), vec![12, 0, 77, 0]);
+    }
+
+    #[test]
+    fn test_line_breakdown() {
+        let input = "xtwone3four\n\nnothing";
+        let calibration = calibration_report(input, &Vocabulary::English, NoDigitsPolicy::Zero).unwrap();
+        let lines: Vec<String> = calibration.lines.iter().map(ToString::to_string).collect();
+
+        assert_eq!(lines, vec![
+            r#"line 1: xtwone3four -> first "two" at 1, last "four" at 7 = 24"#,
+            "line 3: nothing -> no digits = 0",
+        ]);
+    }
+
+    const PORTUGUESE: &[(&str, u8)] = &[
+        ("um", 1),
+        ("dois", 2),
+        ("três", 3),
+        ("quatro", 4),
+        ("cinco", 5),
+        ("seis", 6),
+        ("sete", 7),
+        ("oito", 8),
+        ("nove", 9),
+    ];
+
+    #[test]
+    fn test_scanner_agrees_with_naive_search() {
+        let scanner = Vocabulary::English.scanner();
+        for line in include_str!("../input.txt").lines() {
+            let digits = fetch_first_and_last_digits(&scanner, line)
+                .map(|(first, last)| (first.digit, last.digit))
+                .unwrap_or_default();
+            assert_eq!(digits, naive_first_and_last_digits(line), "{line}");
+        }
+    }
+
+    /// The previous approach: one `find` and one `rfind` per word.
+    pub(crate) fn naive_first_and_last_digits(line: &str) -> (u8, u8) {
+        let first = ENGLISH_DIGIT_NAMES
+            .iter()
+            .chain(NUMERALS)
+            .filter_map(|(name, value)| line.find(name).map(|at| (at, *value)))
+            .min_by_key(|(at, _)| *at);
+
+        let last = ENGLISH_DIGIT_NAMES
+            .iter()
+            .chain(NUMERALS)
+            .filter_map(|(name, value)| line.rfind(name).map(|at| (at, *value)))
+            .max_by_key(|(at, _)| *at);
+
+        match (first, last) {
+            (Some((_, first)), Some((_, last))) => (first, last),
+            _ => Default::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod bench {
+    extern crate test;
+
+    use test::Bencher;
+
+    use crate::{fetch_first_and_last_digits, Vocabulary};
+    use crate::test::naive_first_and_last_digits;
+
+    const INPUT: &str = include_str!("../input.txt");
+
+    #[bench]
+    fn bench_naive_search(b: &mut Bencher) {
+        b.iter(|| {
+            INPUT
+                .lines()
+                .map(naive_first_and_last_digits)
+                .fold(0_u64, |acc, (first, last)| acc + (first * 10 + last) as u64)
+        });
+    }
+
+    #[bench]
+    fn bench_automaton_scan(b: &mut Bencher) {
+        let scanner = Vocabulary::English.scanner();
+        b.iter(|| {
+            INPUT
+                .lines()
+                .filter_map(|line| fetch_first_and_last_digits(&scanner, line))
+                .fold(0_u64, |acc, (first, last)| acc + u64::from(first.digit) * 10 + u64::from(last.digit))
+        });
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+
+/// A digit found in a line, spanning the bytes `start..end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DigitMatch {
+    pub digit: u8,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// An Aho-Corasick automaton over a digit vocabulary. A single pass over a
+/// line reports every occurrence of every word, overlapping ones included, so
+/// `eightwo` yields both `eight` and `two`.
+#[derive(Debug, Clone)]
+pub struct DigitScanner {
+    nodes: Vec<Node>,
+    /// Length and digit of every word, indexed like [`Node::outputs`].
+    words: Vec<(usize, u8)>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    next: HashMap<u8, usize>,
+    /// Longest proper suffix of this node's path that is also in the trie.
+    fail: usize,
+    /// Words ending here, including the ones reachable through `fail`.
+    outputs: Vec<usize>,
+}
+
+const ROOT: usize = 0;
+
+impl DigitScanner {
+    pub fn new(vocabulary: &[(&str, u8)]) -> Self {
+        let mut nodes = vec![Node::default()];
+        let mut words = Vec::with_capacity(vocabulary.len());
+
+        for (word_idx, (word, digit)) in vocabulary.iter().enumerate() {
+            let mut current = ROOT;
+            for &byte in word.as_bytes() {
+                current = match nodes[current].next.get(&byte) {
+                    Some(&next) => next,
+                    None => {
+                        nodes.push(Node::default());
+                        let next = nodes.len() - 1;
+                        nodes[current].next.insert(byte, next);
+                        next
+                    }
+                };
+            }
+            nodes[current].outputs.push(word_idx);
+            words.push((word.len(), *digit));
+        }
+
+        // Breadth first, so every node's failure target is final before its
+        // children need it.
+        let mut queue: Vec<usize> = nodes[ROOT].next.values().copied().collect();
+        let mut head = 0;
+
+        while head < queue.len() {
+            let current = queue[head];
+            head += 1;
+
+            let edges: Vec<(u8, usize)> = nodes[current]
+                .next
+                .iter()
+                .map(|(&byte, &child)| (byte, child))
+                .collect();
+
+            for (byte, child) in edges {
+                let mut fallback = nodes[current].fail;
+                let fail = loop {
+                    match nodes[fallback].next.get(&byte) {
+                        Some(&target) if target != child => break target,
+                        _ if fallback == ROOT => break ROOT,
+                        _ => fallback = nodes[fallback].fail,
+                    }
+                };
+
+                nodes[child].fail = fail;
+                let inherited = nodes[fail].outputs.clone();
+                nodes[child].outputs.extend(inherited);
+                queue.push(child);
+            }
+        }
+
+        Self { nodes, words }
+    }
+
+    /// Every match in the line, ordered by where it ends.
+    pub fn find_iter<'a>(&'a self, line: &'a str) -> impl Iterator<Item = DigitMatch> + 'a {
+        let mut current = ROOT;
+
+        line.bytes().enumerate().flat_map(move |(idx, byte)| {
+            current = self.step(current, byte);
+
+            self.nodes[current].outputs.iter().map(move |&word_idx| {
+                let (len, digit) = self.words[word_idx];
+                DigitMatch {
+                    digit,
+                    start: idx + 1 - len,
+                    end: idx + 1,
+                }
+            })
+        })
+    }
+
+    fn step(&self, mut current: usize, byte: u8) -> usize {
+        loop {
+            if let Some(&next) = self.nodes[current].next.get(&byte) {
+                return next;
+            }
+            if current == ROOT {
+                return ROOT;
+            }
+            current = self.nodes[current].fail;
+        }
+    }
+}
@@ -1,107 +1,242 @@
-use std::collections::HashSet;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashSet};
 
 fn main() {
     const INPUT: &str = include_str!("../input.txt");
-    let result = calculate_galaxy_pairs_distances(INPUT, 1_000_000);
+    let expansion = Expansion::uniform(1_000_000);
+    let result = calculate_galaxy_pairs_distances(INPUT, expansion);
     println!("Result: {result}");
+
+    let universe = parse_galaxies(INPUT);
+    let mut args = std::env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--distance" => {
+                let (id_a, id_b) = (galaxy_id(args.next()), galaxy_id(args.next()));
+                match universe.distance(id_a, id_b, expansion) {
+                    Some(distance) => println!("Distance from {id_a} to {id_b}: {distance}"),
+                    None => println!("No galaxy {id_a} or {id_b}"),
+                }
+            }
+            "--nearest" => {
+                let id = galaxy_id(args.next());
+                print_neighbour("Nearest", id, universe.nearest(id, expansion));
+            }
+            "--farthest" => {
+                let id = galaxy_id(args.next());
+                print_neighbour("Farthest", id, universe.farthest(id, expansion));
+            }
+            "--histogram" => {
+                for (distance, pairs) in universe.distance_histogram(expansion) {
+                    println!("{distance}: {pairs} pairs");
+                }
+            }
+            _ => {
+                eprintln!("Unknown argument `{arg}`, expected --distance <id> <id>, --nearest <id>, --farthest <id> or --histogram");
+                std::process::exit(2);
+            }
+        }
+    }
 }
 
-fn calculate_galaxy_pairs_distances(input: &str, time_dialation: usize) -> usize {
-    let (galaxies, expanded_rows, expanded_cols) = parse_galaxies(input);
+fn print_neighbour(label: &str, id: usize, neighbour: Option<(usize, usize)>) {
+    match neighbour {
+        Some((other, distance)) => println!("{label} to {id}: {other} at {distance}"),
+        None => println!("{label} to {id}: no other galaxy"),
+    }
+}
 
-    let mut distance_sum = 0;
+/// Galaxy ID given after a query flag, exiting if it is missing or invalid.
+fn galaxy_id(arg: Option<String>) -> usize {
+    let arg = arg.unwrap_or_default();
+    arg.parse().unwrap_or_else(|_| {
+        eprintln!("Invalid galaxy ID `{arg}`");
+        std::process::exit(2);
+    })
+}
 
-    for (idx, galaxy_a) in galaxies.iter().enumerate() {
-        for galaxy_b in &galaxies[idx+1..] {
-            let distance = distance_between_galaxies(
-                galaxy_a, galaxy_b,
-                &expanded_rows, &expanded_cols,
-                time_dialation
-            );
+fn calculate_galaxy_pairs_distances(input: &str, expansion: Expansion) -> usize {
+    let galaxies = parse_galaxies(input).expand(expansion);
 
-            distance_sum += distance;
-        }
+    sum_pairwise_distances(galaxies.iter().map(|galaxy| galaxy.x))
+        + sum_pairwise_distances(galaxies.iter().map(|galaxy| galaxy.y))
+}
+
+/// Sum of `|a - b|` over every unordered pair of `values`. Once sorted, each
+/// value is the larger side of a pair with every value before it, so its
+/// contribution is `value * idx - (sum of the values before it)`.
+fn sum_pairwise_distances(values: impl Iterator<Item = usize>) -> usize {
+    let mut values: Vec<_> = values.collect();
+    values.sort_unstable();
+
+    let mut prefix_sum = 0;
+
+    values
+        .iter()
+        .enumerate()
+        .map(|(idx, &value)| {
+            let contribution = value * idx - prefix_sum;
+            prefix_sum += value;
+            contribution
+        })
+        .sum()
+}
+
+/// How many rows or columns each empty row or column becomes. A factor of 0
+/// makes them vanish.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Expansion {
+    cols: usize,
+    rows: usize,
+}
+
+impl Expansion {
+    fn uniform(factor: usize) -> Self {
+        Self { cols: factor, rows: factor }
     }
+}
 
-    distance_sum
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Universe {
+    galaxies: Vec<Coord>,
+    width: usize,
+    height: usize,
+    /// Empty columns, in ascending order.
+    expanded_cols: Vec<usize>,
+    /// Empty rows, in ascending order.
+    expanded_rows: Vec<usize>,
 }
 
-fn distance_between_galaxies(galaxy_a: &Coord, galaxy_b: &Coord, expanded_cols: &HashSet<usize>, expanded_rows: &HashSet<usize>, time_dialation: usize) -> usize {
-    let (min_x, max_x) = min_max(galaxy_a.x, galaxy_b.x);
-    let (min_y, max_y) = min_max(galaxy_a.y, galaxy_b.y);
+impl Universe {
+    /// Maps every galaxy to its coordinates after the universe has expanded,
+    /// so distances become plain Manhattan distances between the returned
+    /// points.
+    fn expand(&self, expansion: Expansion) -> Vec<Coord> {
+        self.galaxies
+            .iter()
+            .map(|galaxy| self.expand_coord(galaxy, expansion))
+            .collect()
+    }
+
+    fn expand_coord(&self, coord: &Coord, expansion: Expansion) -> Coord {
+        expand_with(coord, &self.expanded_cols, &self.expanded_rows, expansion)
+    }
+
+    /// Galaxies are numbered from 1 in reading order, as in the puzzle text.
+    fn galaxy(&self, id: usize) -> Option<&Coord> {
+        id.checked_sub(1).and_then(|idx| self.galaxies.get(idx))
+    }
+
+    fn distance(&self, id_a: usize, id_b: usize, expansion: Expansion) -> Option<usize> {
+        let galaxy_a = self.expand_coord(self.galaxy(id_a)?, expansion);
+        let galaxy_b = self.expand_coord(self.galaxy(id_b)?, expansion);
+        Some(galaxy_a.manhattan_distance(&galaxy_b))
+    }
+
+    /// Every other galaxy as `(id, distance)` from the galaxy `id`.
+    fn distances_from(&self, id: usize, expansion: Expansion) -> Option<Vec<(usize, usize)>> {
+        self.galaxy(id)?;
+
+        let expanded = self.expand(expansion);
+        let origin = expanded[id - 1];
+
+        let distances = expanded
+            .iter()
+            .enumerate()
+            .map(|(idx, galaxy)| (idx + 1, origin.manhattan_distance(galaxy)))
+            .filter(|&(other, _)| other != id)
+            .collect();
+
+        Some(distances)
+    }
+
+    /// The `(id, distance)` of the closest other galaxy, preferring the
+    /// lowest ID on ties. `None` when `id` is unknown or it is alone.
+    fn nearest(&self, id: usize, expansion: Expansion) -> Option<(usize, usize)> {
+        self.distances_from(id, expansion)?
+            .into_iter()
+            .min_by_key(|&(other, distance)| (distance, other))
+    }
+
+    /// The `(id, distance)` of the furthest other galaxy, preferring the
+    /// lowest ID on ties. `None` when `id` is unknown or it is alone.
+    fn farthest(&self, id: usize, expansion: Expansion) -> Option<(usize, usize)> {
+        self.distances_from(id, expansion)?
+            .into_iter()
+            .max_by_key(|&(other, distance)| (distance, Reverse(other)))
+    }
 
-    (min_x+1..=max_x)
-        .map(|x| {
-            if expanded_cols.contains(&x) {
-                time_dialation
-            } else {
-                1
+    /// How many galaxy pairs lie at each distance.
+    fn distance_histogram(&self, expansion: Expansion) -> BTreeMap<usize, usize> {
+        let expanded = self.expand(expansion);
+        let mut histogram = BTreeMap::new();
+
+        for (idx, galaxy_a) in expanded.iter().enumerate() {
+            for galaxy_b in &expanded[idx+1..] {
+                *histogram.entry(galaxy_a.manhattan_distance(galaxy_b)).or_insert(0) += 1;
             }
-        })
-        .chain(
-            (min_y+1..=max_y)
-                .map(|y| {
-                    if expanded_rows.contains(&y) {
-                        time_dialation
-                    } else {
-                        1
-                    }       
-                })
-        )
-        .sum()
+        }
+
+        histogram
+    }
 }
 
-const GALAXY: char = '#';
+/// Each of the `k` empty lines before a coordinate is swapped for `factor`
+/// lines. Those `k` lines are all before it, so `coord - k` can't underflow.
+fn expand_with(coord: &Coord, expanded_cols: &[usize], expanded_rows: &[usize], expansion: Expansion) -> Coord {
+    let cols_before = expanded_cols.partition_point(|&x| x < coord.x);
+    let rows_before = expanded_rows.partition_point(|&y| y < coord.y);
 
-fn parse_galaxies(input: &str) -> (Vec<Coord>, HashSet<usize>, HashSet<usize>) {
+    Coord {
+        x: coord.x - cols_before + cols_before * expansion.cols,
+        y: coord.y - rows_before + rows_before * expansion.rows,
+    }
+}
+
+const GALAXY: char = '#';
 
+fn parse_galaxies(input: &str) -> Universe {
     let lines = input
-        .trim()
         .lines()
+        .map(str::trim)
         .filter(|line| !line.is_empty());
 
+    let mut galaxies = Vec::new();
     let mut cols_with_galaxies = HashSet::new();
+    let mut rows_with_galaxies = HashSet::new();
 
-    let mut galaxies = Vec::new();
+    let mut width = 0;
+    let mut height = 0;
 
-    let mut rows = 0;
-    let mut cols = 0;
+    for (y, line) in lines.enumerate() {
+        height = y + 1;
 
-    let rows_with_galaxies: HashSet<usize> = lines
-        .enumerate()
-        .filter_map(|(y, line)| {
-            cols = cols.max(y);
-            line
-                .trim()
-                .char_indices()
-                .filter(|(_, c)| c == &GALAXY)
-                .fold(None, |_, (x, _)| {
-                    rows = rows.max(x);
-                    cols_with_galaxies.insert(x);
-                    galaxies.push(Coord { x, y });
-                    Some(y)
-                })
-        })
-        .collect();
+        for (x, c) in line.chars().enumerate() {
+            width = width.max(x + 1);
 
-    let expanded_rows = HashSet::from_iter(0..rows)
-        .difference(&rows_with_galaxies)
-        .copied()
-        .collect();
+            if c == GALAXY {
+                cols_with_galaxies.insert(x);
+                rows_with_galaxies.insert(y);
+                galaxies.push(Coord { x, y });
+            }
+        }
+    }
 
-    let expanded_cols = HashSet::from_iter(0..cols)
-        .difference(&cols_with_galaxies)
-        .copied()
+    let expanded_cols = (0..width)
+        .filter(|x| !cols_with_galaxies.contains(x))
         .collect();
 
-    (galaxies, expanded_cols, expanded_rows)
-}
+    let expanded_rows = (0..height)
+        .filter(|y| !rows_with_galaxies.contains(y))
+        .collect();
 
-fn min_max<T: PartialOrd>(a: T, b: T) -> (T, T) {
-    if a > b {
-        (b, a)
-    } else {
-        (a, b)
+    Universe {
+        galaxies,
+        width,
+        height,
+        expanded_cols,
+        expanded_rows,
     }
 }
 
@@ -111,12 +246,17 @@ struct Coord {
     y: usize,
 }
 
+impl Coord {
+    fn manhattan_distance(&self, other: &Coord) -> usize {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use std::collections::HashSet;
     use test_case::test_case;
 
-    use crate::{Coord, parse_galaxies, calculate_galaxy_pairs_distances, distance_between_galaxies};
+    use crate::{Coord, Expansion, Universe, parse_galaxies, calculate_galaxy_pairs_distances, sum_pairwise_distances};
 
 
     const EXAMPLE_INPUT: &str = r"
@@ -146,19 +286,20 @@ mod tests {
             Coord { x: 4, y: 9 },
         ];
 
-        let expected_expanded_rows: HashSet<_> = [
+        let expected_expanded_rows = [
             3, 7
-        ].into();
+        ];
 
-        let expected_expanded_cols: HashSet<_> = [
+        let expected_expanded_cols = [
             2, 5, 8
-        ].into();
+        ];
 
-        let (galaxies, expanded_cols, expanded_rows) = parse_galaxies(EXAMPLE_INPUT);
+        let universe = parse_galaxies(EXAMPLE_INPUT);
 
-        assert_eq!(&galaxies, &expected_galaxies);
-        assert_eq!(expanded_rows, expected_expanded_rows);
-        assert_eq!(expanded_cols, expected_expanded_cols);
+        assert_eq!(&universe.galaxies, &expected_galaxies);
+        assert_eq!((universe.width, universe.height), (10, 10));
+        assert_eq!(universe.expanded_rows, expected_expanded_rows);
+        assert_eq!(universe.expanded_cols, expected_expanded_cols);
     }
 
     #[test_case(5, 9, 2, 9)]
@@ -166,12 +307,13 @@ mod tests {
     #[test_case(3, 6, 2, 17)]
     #[test_case(8, 9, 2, 5)]
     fn test_distance_between_galaxies(idx_a: usize, idx_b: usize, time_dialation: usize, expected_distance: usize) {
-        let (galaxies, expanded_cols, expanded_rows) = parse_galaxies(EXAMPLE_INPUT);
+        let universe = parse_galaxies(EXAMPLE_INPUT);
 
-        let galaxy_a = &galaxies[idx_a-1];
-        let galaxy_b = &galaxies[idx_b-1];
+        let expansion = Expansion::uniform(time_dialation);
+        let galaxy_a = universe.expand_coord(&universe.galaxies[idx_a-1], expansion);
+        let galaxy_b = universe.expand_coord(&universe.galaxies[idx_b-1], expansion);
 
-        let distance = distance_between_galaxies(galaxy_a, galaxy_b, &expanded_cols, &expanded_rows, time_dialation);
+        let distance = galaxy_a.manhattan_distance(&galaxy_b);
 
         assert_eq!(distance, expected_distance);
     }
@@ -180,7 +322,154 @@ mod tests {
     #[test_case(10, 1030)]
     #[test_case(100, 8410)]
     fn example_case(time_dialation: usize, expected_distance_sum: usize) {
-        let result = calculate_galaxy_pairs_distances(EXAMPLE_INPUT, time_dialation);
+        let result = calculate_galaxy_pairs_distances(EXAMPLE_INPUT, Expansion::uniform(time_dialation));
         assert_eq!(result, expected_distance_sum);
     }
-}
\ No newline at end of file
+
+    #[test_case(&[], 0)]
+    #[test_case(&[4], 0)]
+    #[test_case(&[1, 5], 4)]
+    #[test_case(&[7, 1, 4, 4], 18)]
+    fn test_sum_pairwise_distances(values: &[usize], expected_sum: usize) {
+        assert_eq!(sum_pairwise_distances(values.iter().copied()), expected_sum);
+    }
+
+    /// Reference distance that steps over every row and column between the
+    /// two galaxies, paying the expansion factor for the empty ones.
+    fn walked_distance(galaxy_a: &Coord, galaxy_b: &Coord, universe: &Universe, expansion: Expansion) -> usize {
+        let cols = galaxy_a.x.min(galaxy_b.x)+1..=galaxy_a.x.max(galaxy_b.x);
+        let rows = galaxy_a.y.min(galaxy_b.y)+1..=galaxy_a.y.max(galaxy_b.y);
+
+        cols.map(|x| if universe.expanded_cols.contains(&x) { expansion.cols } else { 1 })
+            .chain(rows.map(|y| if universe.expanded_rows.contains(&y) { expansion.rows } else { 1 }))
+            .sum()
+    }
+
+    #[test_case(EXAMPLE_INPUT, 2, 2)]
+    #[test_case(EXAMPLE_INPUT, 10, 1_000_000)]
+    #[test_case(WIDE_INPUT, 10, 100)]
+    #[test_case(TALL_INPUT, 1, 3)]
+    #[test_case(TALL_INPUT, 7, 1)]
+    #[test_case(EXAMPLE_INPUT, 0, 0)]
+    #[test_case(WIDE_INPUT, 0, 5)]
+    #[test_case(TALL_INPUT, 2, 0)]
+    fn expanded_coordinates_match_walked_distances(input: &str, cols: usize, rows: usize) {
+        let universe = parse_galaxies(input);
+        let expansion = Expansion { cols, rows };
+        let expanded = universe.expand(expansion);
+
+        for (idx, galaxy_a) in universe.galaxies.iter().enumerate() {
+            for (jdx, galaxy_b) in universe.galaxies.iter().enumerate().skip(idx + 1) {
+                let walked = walked_distance(galaxy_a, galaxy_b, &universe, expansion);
+                let direct = expanded[idx].manhattan_distance(&expanded[jdx]);
+
+                assert_eq!(walked, direct);
+            }
+        }
+    }
+
+    const WIDE_INPUT: &str = r"
+        #.....
+        ......
+        ...#..
+    ";
+
+    const TALL_INPUT: &str = r"
+        #.
+        ..
+        ..
+        .#
+        ..
+    ";
+
+    #[test]
+    fn non_square_universes() {
+        let wide = parse_galaxies(WIDE_INPUT);
+        assert_eq!((wide.width, wide.height), (6, 3));
+        assert_eq!(wide.expanded_cols, [1, 2, 4, 5]);
+        assert_eq!(wide.expanded_rows, [1]);
+
+        let tall = parse_galaxies(TALL_INPUT);
+        assert_eq!((tall.width, tall.height), (2, 5));
+        assert!(tall.expanded_cols.is_empty());
+        assert_eq!(tall.expanded_rows, [1, 2, 4]);
+    }
+
+    #[test_case(WIDE_INPUT, Expansion { cols: 10, rows: 100 }, 3 + 2 * 9 + 2 + 99)]
+    #[test_case(WIDE_INPUT, Expansion { cols: 100, rows: 10 }, 3 + 2 * 99 + 2 + 9)]
+    #[test_case(TALL_INPUT, Expansion { cols: 1_000, rows: 3 }, 1 + 3 + 2 * 2)]
+    #[test_case(TALL_INPUT, Expansion::uniform(1), 4)]
+    #[test_case(WIDE_INPUT, Expansion::uniform(0), 1 + 1)]
+    #[test_case(TALL_INPUT, Expansion { cols: 5, rows: 0 }, 1 + 1)]
+    fn per_axis_expansion(input: &str, expansion: Expansion, expected_distance: usize) {
+        assert_eq!(calculate_galaxy_pairs_distances(input, expansion), expected_distance);
+    }
+
+    #[test]
+    fn many_galaxies() {
+        let size = 400;
+        let input: String = (0..size)
+            .map(|y| {
+                (0..size)
+                    .map(|x| if (x * 7 + y * 13) % 11 == 0 && y % 5 != 2 { '#' } else { '.' })
+                    .collect::<String>()
+                    + "\n"
+            })
+            .collect();
+
+        assert!(parse_galaxies(&input).galaxies.len() > 10_000);
+
+        let ones = calculate_galaxy_pairs_distances(&input, Expansion::uniform(1));
+        let twos = calculate_galaxy_pairs_distances(&input, Expansion::uniform(2));
+        let millions = calculate_galaxy_pairs_distances(&input, Expansion::uniform(1_000_000));
+
+        assert!(ones < twos);
+        assert_eq!(millions - ones, (twos - ones) * 999_999);
+    }
+
+    #[test_case(5, 9, Some(9))]
+    #[test_case(9, 5, Some(9))]
+    #[test_case(1, 7, Some(15))]
+    #[test_case(4, 4, Some(0))]
+    #[test_case(0, 4, None)]
+    #[test_case(1, 10, None)]
+    fn test_distance_by_id(id_a: usize, id_b: usize, expected_distance: Option<usize>) {
+        let universe = parse_galaxies(EXAMPLE_INPUT);
+        assert_eq!(universe.distance(id_a, id_b, Expansion::uniform(2)), expected_distance);
+    }
+
+    #[test_case(1, Some((2, 6)), Some((6, 15)))]
+    #[test_case(2, Some((4, 5)), Some((8, 19)))]
+    #[test_case(5, Some((3, 5)), Some((2, 13)))]
+    #[test_case(9, Some((7, 5)), Some((2, 14)))]
+    #[test_case(10, None, None)]
+    fn test_nearest_and_farthest(id: usize, expected_nearest: Option<(usize, usize)>, expected_farthest: Option<(usize, usize)>) {
+        let universe = parse_galaxies(EXAMPLE_INPUT);
+
+        assert_eq!(universe.nearest(id, Expansion::uniform(2)), expected_nearest);
+        assert_eq!(universe.farthest(id, Expansion::uniform(2)), expected_farthest);
+    }
+
+    #[test]
+    fn lonely_galaxy_has_no_neighbours() {
+        let universe = parse_galaxies("..\n.#");
+
+        assert_eq!(universe.nearest(1, Expansion::uniform(2)), None);
+        assert_eq!(universe.farthest(1, Expansion::uniform(2)), None);
+        assert!(universe.distance_histogram(Expansion::uniform(2)).is_empty());
+    }
+
+    #[test]
+    fn test_distance_histogram() {
+        let universe = parse_galaxies(EXAMPLE_INPUT);
+        let histogram = universe.distance_histogram(Expansion::uniform(2));
+
+        let expected_histogram = [
+            (5, 4), (6, 6), (8, 1), (9, 7), (10, 2), (11, 2), (12, 3),
+            (13, 1), (14, 3), (15, 3), (16, 1), (17, 2), (19, 1),
+        ].into();
+
+        assert_eq!(histogram, expected_histogram);
+        assert_eq!(histogram.iter().map(|(distance, count)| distance * count).sum::<usize>(), 374);
+    }
+}
@@ -1,93 +1,337 @@
-#![feature(array_windows)]
+use std::{fmt, str::FromStr};
 
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
-fn main() {
+fn main() -> Result<(), PatternError> {
     const INPUT: &str = include_str!("../input.txt");
-    let result = summarize_reflections(INPUT);
-    println!("Result: {result}");
+
+    for part in [Part::One, Part::Two] {
+        let summary = summarize_reflections(INPUT, part)?;
+
+        for diagnostic in &summary.diagnostics {
+            eprintln!("{diagnostic}");
+        }
+
+        println!("Part {}: {}", part.smudges() + 1, summary.total);
+    }
+
+    Ok(())
 }
 
-fn summarize_reflections(input: &str) -> usize {
-    let patterns: Vec<Vec<&str>> = input
-        .trim()
-        .split("\n\n")
-        .map(|pat| {
-            pat.lines().collect()
+fn summarize_reflections(input: &str, part: Part) -> Result<Summary, PatternError> {
+    let patterns = parse_patterns(input)?;
+
+    let reflections: Vec<Vec<Reflection>> = patterns
+        .into_par_iter()
+        .map(|pattern| find_reflections(&pattern, part.smudges()))
+        .map(|mirrors| mirrors.into_iter().map(|mirror| mirror.reflection).collect())
+        .collect();
+
+    let total = reflections
+        .iter()
+        .flatten()
+        .map(|reflection| reflection.summary())
+        .sum();
+
+    let diagnostics = reflections
+        .into_iter()
+        .enumerate()
+        .filter_map(|(idx, reflections)| match reflections.len() {
+            0 => Some(Diagnostic::NoReflection { pattern: idx + 1 }),
+            1 => None,
+            _ => Some(Diagnostic::MultipleReflections { pattern: idx + 1, reflections }),
         })
         .collect();
 
-    patterns
-        .into_par_iter()
-        .map(get_reflections)
-        .map(|r| r.into_iter().map(Reflection::summary).sum::<usize>())
-        .sum()
+    Ok(Summary { total, diagnostics })
+}
+
+/// Splits the input into patterns on blank lines, accepting both `\n` and
+/// `\r\n` line endings.
+fn parse_patterns(input: &str) -> Result<Vec<Pattern>, PatternError> {
+    let mut patterns = Vec::new();
+    let mut block = Vec::new();
+    let mut first_line = 0;
+
+    for (idx, line) in input.lines().chain([""]).enumerate() {
+        if !line.trim().is_empty() {
+            if block.is_empty() {
+                first_line = idx;
+            }
+            block.push(line);
+            continue;
+        }
+
+        if block.is_empty() {
+            continue;
+        }
+
+        match Pattern::parse(&block) {
+            Ok(pattern) => patterns.push(pattern),
+            Err(error) => return Err(PatternError {
+                pattern: patterns.len() + 1,
+                line: first_line + error.row().unwrap_or(0) + 1,
+                error,
+            }),
+        }
+
+        block.clear();
+    }
+
+    Ok(patterns)
 }
 
-fn get_reflections(pattern: Vec<&str>) -> Vec<Reflection> {
-    let mut reflections = Vec::new();
+/// Finds every mirror line across which exactly `smudges` cells differ from
+/// their reflection. Each smudge is reported by the coordinate of the cell on
+/// the upper (or left) side of the line; flipping either cell of the pair
+/// fixes it.
+fn find_reflections(pattern: &Pattern, smudges: usize) -> Vec<Mirror> {
+    let horizontal = mirror_lines(&pattern.rows, smudges)
+        .into_iter()
+        .map(|(line, found)| Mirror {
+            reflection: Reflection::Horizontal(line),
+            smudges: found.into_iter().map(|(row, col)| Coord { row, col }).collect(),
+        });
 
-    let upper_bound = 0;
-    let lower_bound = pattern.len();
+    let vertical = mirror_lines(&pattern.cols, smudges)
+        .into_iter()
+        .map(|(line, found)| Mirror {
+            reflection: Reflection::Vertical(line),
+            smudges: found.into_iter().map(|(col, row)| Coord { row, col }).collect(),
+        });
 
-    for (up_idx, [up, down]) in pattern.array_windows().enumerate() {
-        if up == down {
-            let mut upper_idx = up_idx as isize - 1;
-            let mut lower_idx = up_idx + 2;
+    horizontal.chain(vertical).collect()
+}
 
-            let mut is_reflecting = true;
+/// Mirror lines between consecutive `lines` with exactly `smudges` differing
+/// bits, along with `(line, bit)` of each difference on the near side.
+fn mirror_lines(lines: &BitLines, smudges: usize) -> Vec<(usize, Vec<(usize, usize)>)> {
+    let len = lines.len();
+    let mut mirrors = Vec::new();
 
-            while is_reflecting && upper_idx >= upper_bound as isize && lower_idx < lower_bound {
-                is_reflecting = pattern[upper_idx as usize] == pattern[lower_idx];
-                upper_idx -= 1;
-                lower_idx += 1;
-            }
+    for line in 1..len {
+        let pairs = (0..line.min(len - line)).map(|offset| (line - 1 - offset, line + offset));
 
-            if is_reflecting {
-                reflections.push(Reflection::Horizontal(up_idx + 1));
+        let mut differences = 0;
+        for (a, b) in pairs.clone() {
+            differences += lines.differences(a, b);
+            if differences > smudges {
+                break;
             }
         }
+
+        if differences == smudges {
+            let found = pairs
+                .flat_map(|(a, b)| lines.differing(a, b).map(move |bit| (a, bit)))
+                .collect();
+
+            mirrors.push((line, found));
+        }
     }
 
-    let left_bound = 0;
-    let right_bound = pattern[0].len();
+    mirrors
+}
+
+const ROCK: char = '#';
+const ASH: char = '.';
+
+/// A pattern stored twice, once as row bitmasks and once as column bitmasks,
+/// so both mirror directions compare lines as integers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pattern {
+    rows: BitLines,
+    cols: BitLines,
+}
+
+impl Pattern {
+    /// Builds a pattern from its rows, which must all be the same non-zero
+    /// width and only hold `#` and `.`.
+    fn parse(lines: &[&str]) -> Result<Self, InvalidPattern> {
+        let height = lines.len();
+        let width = lines.first().map_or(0, |line| line.chars().count());
+
+        if width == 0 {
+            return Err(InvalidPattern::Empty);
+        }
 
-    for (left_idx, right_idx) in (left_bound..right_bound-1).zip(left_bound+1..right_bound) {
-        if compare_vertically(&pattern, left_idx, right_idx, upper_bound, lower_bound) {
-            let mut inner_left_idx = left_idx as isize - 1;
-            let mut inner_right_idx = right_idx + 1;
+        let mut rows = BitLines::new(height, width);
+        let mut cols = BitLines::new(width, height);
 
-            let mut is_reflecting = true;
+        for (row, line) in lines.iter().enumerate() {
+            let found = line.chars().count();
+            if found != width {
+                return Err(InvalidPattern::RaggedRow { row, expected: width, found });
+            }
 
-            while is_reflecting && inner_left_idx >= left_bound as isize && inner_right_idx < right_bound {
-                is_reflecting = compare_vertically(&pattern, inner_left_idx as usize, inner_right_idx, upper_bound, lower_bound);
-                inner_left_idx -= 1;
-                inner_right_idx += 1;
+            for (col, c) in line.chars().enumerate() {
+                match c {
+                    ROCK => {
+                        rows.set(row, col);
+                        cols.set(col, row);
+                    }
+                    ASH => {}
+                    _ => return Err(InvalidPattern::InvalidChar { row, col, c }),
+                }
             }
+        }
+
+        Ok(Self { rows, cols })
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = InvalidPattern;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines: Vec<_> = s.lines().filter(|line| !line.trim().is_empty()).collect();
+        Self::parse(&lines)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InvalidPattern {
+    Empty,
+    RaggedRow { row: usize, expected: usize, found: usize },
+    InvalidChar { row: usize, col: usize, c: char },
+}
+
+impl InvalidPattern {
+    fn row(&self) -> Option<usize> {
+        match self {
+            InvalidPattern::Empty => None,
+            InvalidPattern::RaggedRow { row, .. } | InvalidPattern::InvalidChar { row, .. } => Some(*row),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(dead_code)]
+struct PatternError {
+    /// 1-based, in input order.
+    pattern: usize,
+    /// 1-based input line of the offending row.
+    line: usize,
+    error: InvalidPattern,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Summary {
+    total: usize,
+    diagnostics: Vec<Diagnostic>,
+}
 
-            if is_reflecting {
-                reflections.push(Reflection::Vertical(left_idx + 1));
+/// Patterns that don't have exactly one mirror line. They still add every
+/// reflection they do have to the total.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Diagnostic {
+    NoReflection { pattern: usize },
+    MultipleReflections { pattern: usize, reflections: Vec<Reflection> },
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Diagnostic::NoReflection { pattern } => write!(f, "pattern {pattern} has no reflection"),
+            Diagnostic::MultipleReflections { pattern, reflections } => {
+                write!(f, "pattern {pattern} has {} reflections: ", reflections.len())?;
+                for (idx, reflection) in reflections.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ", ")?;
+                    }
+                    match reflection {
+                        Reflection::Horizontal(row) => write!(f, "horizontal after row {row}")?,
+                        Reflection::Vertical(col) => write!(f, "vertical after column {col}")?,
+                    }
+                }
+                Ok(())
             }
         }
     }
+}
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// Fixed-width bitsets laid out back to back, `stride` words per line, so
+/// patterns wider than 64 cells still work.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BitLines {
+    len: usize,
+    stride: usize,
+    words: Vec<u64>,
+}
+
+impl BitLines {
+    fn new(len: usize, bits: usize) -> Self {
+        let stride = bits.div_ceil(WORD_BITS);
+        Self { len, stride, words: vec![0; len * stride] }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn set(&mut self, line: usize, bit: usize) {
+        self.words[line * self.stride + bit / WORD_BITS] |= 1 << (bit % WORD_BITS);
+    }
+
+    fn line(&self, line: usize) -> &[u64] {
+        &self.words[line * self.stride..(line + 1) * self.stride]
+    }
+
+    fn differences(&self, a: usize, b: usize) -> usize {
+        self.line(a)
+            .iter()
+            .zip(self.line(b))
+            .map(|(a, b)| (a ^ b).count_ones() as usize)
+            .sum()
+    }
 
-    reflections
+    fn differing(&self, a: usize, b: usize) -> impl Iterator<Item = usize> + '_ {
+        self.line(a)
+            .iter()
+            .zip(self.line(b))
+            .enumerate()
+            .flat_map(|(word_idx, (a, b))| {
+                let mut diff = a ^ b;
+                std::iter::from_fn(move || {
+                    (diff != 0).then(|| {
+                        let bit = diff.trailing_zeros() as usize;
+                        diff &= diff - 1;
+                        word_idx * WORD_BITS + bit
+                    })
+                })
+            })
+    }
 }
 
-fn compare_vertically(m: &[&str], left_idx: usize, right_idx: usize, upper_bound: usize, lower_bound: usize) -> bool {
-    // (upper_bound..lower_bound).all(|row_idx| {
-    //     m[row_idx].chars().nth(left_idx) == m[row_idx].chars().nth(right_idx)
-    // })
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Part {
+    One,
+    Two,
+}
 
-    for row_idx in upper_bound..lower_bound {
-        if m[row_idx].chars().nth(left_idx) != m[row_idx].chars().nth(right_idx) {
-            return false;
+impl Part {
+    fn smudges(self) -> usize {
+        match self {
+            Part::One => 0,
+            Part::Two => 1,
         }
     }
+}
 
-    true
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Coord {
+    row: usize,
+    col: usize,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Mirror {
+    reflection: Reflection,
+    smudges: Vec<Coord>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Reflection {
     Horizontal(usize),
     Vertical(usize),
@@ -104,9 +348,9 @@ impl Reflection {
 
 #[cfg(test)]
 mod tests {
-    use rayon::{str::ParallelString, iter::ParallelIterator};
+    use test_case::test_case;
 
-    use crate::summarize_reflections;
+    use crate::{Coord, Diagnostic, InvalidPattern, Mirror, Part, Pattern, PatternError, Reflection, Summary, find_reflections, parse_patterns, summarize_reflections};
 
     const EXAMPLE_INPUT: &str = r"
 #.##..##.
@@ -126,10 +370,119 @@ mod tests {
 #....#..#
 ";
 
+    #[test_case(Part::One, 405)]
+    #[test_case(Part::Two, 400)]
+    fn example_case(part: Part, expected_output: usize) {
+        let result = summarize_reflections(EXAMPLE_INPUT, part);
+        assert_eq!(result, Ok(Summary { total: expected_output, diagnostics: vec![] }));
+    }
+
+    fn example_pattern(idx: usize) -> Pattern {
+        parse_patterns(EXAMPLE_INPUT).unwrap().swap_remove(idx)
+    }
+
+    #[test_case(0, 0, Mirror { reflection: Reflection::Vertical(5), smudges: vec![] })]
+    #[test_case(1, 0, Mirror { reflection: Reflection::Horizontal(4), smudges: vec![] })]
+    #[test_case(0, 1, Mirror { reflection: Reflection::Horizontal(3), smudges: vec![Coord { row: 0, col: 0 }] })]
+    #[test_case(1, 1, Mirror { reflection: Reflection::Horizontal(1), smudges: vec![Coord { row: 0, col: 4 }] })]
+    fn test_find_reflections(pattern_idx: usize, smudges: usize, expected_mirror: Mirror) {
+        let mirrors = find_reflections(&example_pattern(pattern_idx), smudges);
+        assert_eq!(mirrors, vec![expected_mirror]);
+    }
+
+    #[test]
+    fn smudges_must_match_exactly() {
+        let pattern = Pattern::parse(&["#..#", "#..#", "...."]).unwrap();
+
+        assert_eq!(find_reflections(&pattern, 0), vec![
+            Mirror { reflection: Reflection::Horizontal(1), smudges: vec![] },
+            Mirror { reflection: Reflection::Vertical(2), smudges: vec![] },
+        ]);
+
+        assert_eq!(find_reflections(&pattern, 2), vec![
+            Mirror { reflection: Reflection::Horizontal(2), smudges: vec![Coord { row: 1, col: 0 }, Coord { row: 1, col: 3 }] },
+            Mirror { reflection: Reflection::Vertical(1), smudges: vec![Coord { row: 0, col: 0 }, Coord { row: 1, col: 0 }] },
+            Mirror { reflection: Reflection::Vertical(3), smudges: vec![Coord { row: 0, col: 2 }, Coord { row: 1, col: 2 }] },
+        ]);
+
+        assert_eq!(find_reflections(&pattern, 5), vec![]);
+    }
+
+    #[test]
+    fn wide_patterns_span_several_words() {
+        let left: String = (0..70).map(|i: u64| if (i * i * i + i + 5) % 7 < 3 { '#' } else { '.' }).collect();
+        let row = left.clone() + &left.chars().rev().collect::<String>();
+        let smudged: String = row.char_indices().map(|(col, c)| match (col, c) {
+            (100, '#') => '.',
+            (100, _) => '#',
+            _ => c,
+        }).collect();
+
+        let pattern = Pattern::parse(&[&row, &row]).unwrap();
+        assert_eq!(find_reflections(&pattern, 0), vec![
+            Mirror { reflection: Reflection::Horizontal(1), smudges: vec![] },
+            Mirror { reflection: Reflection::Vertical(70), smudges: vec![] },
+        ]);
+
+        let pattern = Pattern::parse(&[&row, &smudged]).unwrap();
+        assert_eq!(find_reflections(&pattern, 1), vec![
+            Mirror { reflection: Reflection::Horizontal(1), smudges: vec![Coord { row: 0, col: 100 }] },
+            Mirror { reflection: Reflection::Vertical(70), smudges: vec![Coord { row: 1, col: 39 }] },
+        ]);
+    }
+
+    #[test]
+    fn windows_line_endings() {
+        let crlf = EXAMPLE_INPUT.replace('\n', "\r\n");
+
+        assert_eq!(parse_patterns(&crlf), parse_patterns(EXAMPLE_INPUT));
+        assert_eq!(summarize_reflections(&crlf, Part::Two).map(|summary| summary.total), Ok(400));
+    }
+
+    #[test_case("", Err(InvalidPattern::Empty))]
+    #[test_case("#.\n##\r\n", Ok(()))]
+    #[test_case("#.#\n##\n", Err(InvalidPattern::RaggedRow { row: 1, expected: 3, found: 2 }))]
+    #[test_case("#.\n##.\n", Err(InvalidPattern::RaggedRow { row: 1, expected: 2, found: 3 }))]
+    #[test_case("#.\n#O\n", Err(InvalidPattern::InvalidChar { row: 1, col: 1, c: 'O' }))]
+    #[test_case("#. \n", Err(InvalidPattern::InvalidChar { row: 0, col: 2, c: ' ' }))]
+    fn test_parse_pattern(input: &str, expected: Result<(), InvalidPattern>) {
+        assert_eq!(input.parse::<Pattern>().map(|_| ()), expected);
+    }
+
+    #[test]
+    fn errors_point_at_the_input_line() {
+        let input = "#.\n..\n\n\n##\n#\n";
+
+        assert_eq!(parse_patterns(input), Err(PatternError {
+            pattern: 2,
+            line: 6,
+            error: InvalidPattern::RaggedRow { row: 1, expected: 2, found: 1 },
+        }));
+
+        assert_eq!(parse_patterns("\n\n"), Ok(vec![]));
+    }
+
     #[test]
-    fn example_case() {
-        const EXPECTED_OUTPUT: usize = 405;
-        let result = summarize_reflections(EXAMPLE_INPUT);
-        assert_eq!(result, EXPECTED_OUTPUT);
-    }    
-}
\ No newline at end of file
+    fn ambiguous_patterns_are_diagnosed() {
+        let input = "#..#\n#..#\n....\n\n#.\n..\n\n#.#\n#.#\n";
+
+        let summary = summarize_reflections(input, Part::One).unwrap();
+
+        assert_eq!(summary.total, 100 + 2 + 100);
+        assert_eq!(summary.diagnostics, vec![
+            Diagnostic::MultipleReflections {
+                pattern: 1,
+                reflections: vec![Reflection::Horizontal(1), Reflection::Vertical(2)],
+            },
+            Diagnostic::NoReflection { pattern: 2 },
+        ]);
+
+        assert_eq!(
+            summary.diagnostics.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            [
+                "pattern 1 has 2 reflections: horizontal after row 1, vertical after column 2",
+                "pattern 2 has no reflection",
+            ]
+        );
+    }
+}
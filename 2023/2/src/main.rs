@@ -1,75 +1,130 @@
-use std::{str::FromStr, collections::HashMap, convert::Infallible};
+use std::{str::FromStr, collections::{HashMap, hash_map::Entry}, num::ParseIntError};
 
-use strum::EnumString;
-
-fn main() {
+fn main() -> Result<(), Vec<LineError>> {
     const INPUT: &str = include_str!("../input.txt");
-    let result = cube_conundrum_part_2(INPUT);
-    println!("Result: {result}");
+
+    let args: Vec<String> = std::env::args().collect();
+    let bag_args: Vec<&str> = args
+        .windows(2)
+        .filter(|pair| pair[0] == "--bag")
+        .map(|pair| pair[1].as_str())
+        .collect();
+    let bags: Vec<Bag> = bag_args
+        .iter()
+        .map(|bag| bag.parse().unwrap_or_else(|error| {
+            eprintln!("Invalid --bag `{bag}`: {error:?}");
+            std::process::exit(2);
+        }))
+        .collect();
+
+    let part_1 = cube_conundrum(INPUT)?;
+    let part_2 = cube_conundrum_part_2(INPUT)?;
+    println!("Part 1: {part_1}");
+    println!("Part 2: {part_2}");
+
+    if !bags.is_empty() {
+        let games = parse_games(INPUT, Some(&PUZZLE_COLORS))?;
+        for (bag, ids) in bag_args.iter().zip(possible_games(&games, &bags)) {
+            println!("Possible with {bag}: {ids:?}");
+        }
+    }
+
+    Ok(())
 }
 
-fn cube_conundrum(input: &str) -> i32 {
-    let games = parse_games(input);
-    sum_valid_games(games)
+fn cube_conundrum(input: &str) -> Result<i32, Vec<LineError>> {
+    let games = parse_games(input, Some(&PUZZLE_COLORS))?;
+    Ok(sum_valid_games(games, &Bag::puzzle()))
 }
 
-fn cube_conundrum_part_2(input: &str) -> i32 {
-    let games = parse_games(input);
-    sum_minimum_powers(games)
+fn cube_conundrum_part_2(input: &str) -> Result<i32, Vec<LineError>> {
+    let games = parse_games(input, Some(&PUZZLE_COLORS))?;
+    Ok(sum_minimum_powers(games, &PUZZLE_COLORS))
 }
 
-fn parse_games(input: &str) -> Vec<Game> {
-    input
-        .lines()
-        .filter_map(|line| line.trim().parse().ok())
-        .collect()
+/// Parses every non-blank line, failing with the errors of every line that
+/// isn't a valid game. With `colors`, any other color is an error.
+fn parse_games(input: &str, colors: Option<&[&str]>) -> Result<Vec<Game>, Vec<LineError>> {
+    let (games, errors) = parse_games_lenient(input, colors);
+
+    if errors.is_empty() {
+        Ok(games)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Like [`parse_games`], but keeps every valid game and returns the lines it
+/// had to skip alongside them.
+fn parse_games_lenient(input: &str, colors: Option<&[&str]>) -> (Vec<Game>, Vec<LineError>) {
+    let mut games = Vec::new();
+    let mut skipped = Vec::new();
+
+    for (idx, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        match Game::parse(line, colors) {
+            Ok(game) => games.push(game),
+            Err(error) => skipped.push(LineError { line: idx + 1, error }),
+        }
+    }
+
+    (games, skipped)
 }
 
-fn sum_valid_games<T: IntoIterator<Item = Game>>(games: T) -> i32 {
+/// A line that isn't a valid game. Line numbers start at 1 and include the
+/// blank lines that were skipped.
+#[derive(Debug, PartialEq, Eq)]
+#[allow(dead_code)]
+struct LineError {
+    line: usize,
+    error: NotAGame,
+}
+
+fn sum_valid_games<T: IntoIterator<Item = Game>>(games: T, bag: &Bag) -> i32 {
     games
         .into_iter()
-        .filter(game_is_valid)
+        .filter(|game| bag.allows(game))
         .map(|game| game.id)
         .sum()
 }
 
-fn game_is_valid(game: &Game) -> bool {
-    for set in &game.sets {
-        if set.red > MAX_RED_CUBE_COUNT ||
-            set.green > MAX_GREEN_CUBE_COUNT ||
-            set.blue > MAX_BLUE_CUBE_COUNT
-        {
-            return false;
-        }
-    }
-
-    true
+/// For every bag, the IDs of the games it could have been used for.
+fn possible_games(games: &[Game], bags: &[Bag]) -> Vec<Vec<i32>> {
+    bags
+        .iter()
+        .map(|bag| {
+            games
+                .iter()
+                .filter(|game| bag.allows(game))
+                .map(|game| game.id)
+                .collect()
+        })
+        .collect()
 }
 
-fn sum_minimum_powers<T: IntoIterator<Item = Game>>(games: T) -> i32 {
+fn sum_minimum_powers<T: IntoIterator<Item = Game>>(games: T, colors: &[&str]) -> i32 {
     games
         .into_iter()
-        .map(get_minimum_power)
+        .map(|game| get_minimum_power(&game, colors))
         .sum()
 }
 
-fn get_minimum_power(game: Game) -> i32 {
-    let mut minimum_red = 0;
-    let mut minimum_green = 0;
-    let mut minimum_blue = 0;
+/// Multiplies the fewest cubes of each of `colors` the game could have been
+/// played with. A color the game never shows contributes a zero.
+fn get_minimum_power(game: &Game, colors: &[&str]) -> i32 {
+    let minimum_bag = game.minimum_bag();
 
-    for set in game.sets {
-        minimum_red = minimum_red.max(set.red);
-        minimum_green = minimum_green.max(set.green);
-        minimum_blue = minimum_blue.max(set.blue);
-    }
-
-    minimum_red * minimum_green * minimum_blue
+    colors
+        .iter()
+        .map(|color| minimum_bag.count(color))
+        .product()
 }
 
-const MAX_RED_CUBE_COUNT: i32 = 12;
-const MAX_GREEN_CUBE_COUNT: i32 = 13;
-const MAX_BLUE_CUBE_COUNT: i32 = 14;
+const PUZZLE_COLORS: [&str; 3] = ["red", "green", "blue"];
 
 #[derive(Debug, PartialEq, Eq)]
 struct Game {
@@ -77,74 +132,187 @@ struct Game {
     sets: Vec<Set>,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+impl Game {
+    /// The smallest bag holding enough cubes of every color for every set.
+    fn minimum_bag(&self) -> Bag {
+        let mut cubes = HashMap::new();
+
+        for (color, &count) in self.sets.iter().flat_map(|set| &set.cubes) {
+            let minimum = cubes.entry(color.clone()).or_insert(0);
+            *minimum = count.max(*minimum);
+        }
+
+        Bag { cubes }
+    }
+
+    fn parse(s: &str, colors: Option<&[&str]>) -> Result<Self, NotAGame> {
+        let (id_part, set_part) = s.split_once(':').ok_or(NotAGame::ImpossibleToSplitAtColon)?;
+        let id_part = id_part.trim();
+        let (prefix, id) = id_part.split_once(' ').unwrap_or((id_part, ""));
+        if prefix != "Game" {
+            return Err(NotAGame::MissingGamePrefix);
+        }
+
+        let id = id
+            .trim()
+            .parse()
+            .ok()
+            .filter(|id| *id >= 0)
+            .ok_or(NotAGame::NoId)?;
+
+        let sets: Vec<Set> = set_part
+            .split(';')
+            .map(|set| Set::parse(set, colors))
+            .collect::<Result<_, _>>()
+            .map_err(NotAGame::InvalidSet)?;
+
+        Ok(Self {
+            id,
+            sets
+        })
+    }
+}
+
+/// Cubes revealed in one handful, by color. Colors are stored lowercase.
+#[derive(Debug, PartialEq, Eq, Default)]
 struct Set {
-    red: i32,
-    green: i32,
-    blue: i32,
+    cubes: HashMap<String, i32>,
+}
+
+impl Set {
+    #[cfg(test)]
+    fn count(&self, color: &str) -> i32 {
+        self.cubes.get(color).copied().unwrap_or_default()
+    }
+
+    fn parse(s: &str, colors: Option<&[&str]>) -> Result<Self, NotASet> {
+        let mut cubes = HashMap::new();
+
+        for count_and_color in s.split(',') {
+            let (count, color) = count_and_color
+                .trim()
+                .split_once(' ')
+                .ok_or_else(|| NotASet::NotCountAndColor(count_and_color.trim().to_owned()))?;
+
+            let count: i32 = count.parse().map_err(NotASet::InvalidCount)?;
+            if count < 0 {
+                return Err(NotASet::NegativeCount(count));
+            }
+
+            let color = color.trim().to_lowercase();
+            let is_known = match colors {
+                Some(colors) => colors.iter().any(|known| known.eq_ignore_ascii_case(&color)),
+                None => color.chars().all(char::is_alphabetic),
+            };
+            if !is_known {
+                return Err(NotASet::UnknownColor(color));
+            }
+
+            match cubes.entry(color) {
+                Entry::Occupied(entry) => return Err(NotASet::DuplicateColor(entry.key().clone())),
+                Entry::Vacant(entry) => entry.insert(count),
+            };
+        }
+
+        Ok(Self { cubes })
+    }
+}
+
+impl<const N: usize> From<[(&str, i32); N]> for Set {
+    fn from(cubes: [(&str, i32); N]) -> Self {
+        Self { cubes: lowercase_colors(cubes) }
+    }
+}
+
+fn lowercase_colors<const N: usize>(cubes: [(&str, i32); N]) -> HashMap<String, i32> {
+    cubes
+        .into_iter()
+        .map(|(color, count)| (color.to_lowercase(), count))
+        .collect()
 }
 
-#[derive(EnumString, PartialEq, Eq, Hash)]
-#[strum(ascii_case_insensitive)]
-enum Color {
-    Red,
-    Green,
-    Blue,
+/// How many cubes of each color are in the bag. Colors are stored lowercase
+/// and any color missing from the bag has no cubes at all.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+struct Bag {
+    cubes: HashMap<String, i32>,
+}
+
+impl Bag {
+    /// The bag from part 1: 12 red, 13 green and 14 blue cubes.
+    fn puzzle() -> Self {
+        Self::from([("red", 12), ("green", 13), ("blue", 14)])
+    }
+
+    fn count(&self, color: &str) -> i32 {
+        self.cubes.get(color).copied().unwrap_or_default()
+    }
+
+    /// Whether every set of the game could have been drawn from this bag.
+    fn allows(&self, game: &Game) -> bool {
+        game.sets
+            .iter()
+            .flat_map(|set| &set.cubes)
+            .all(|(color, &count)| count <= self.count(color))
+    }
+}
+
+impl<const N: usize> From<[(&str, i32); N]> for Bag {
+    fn from(cubes: [(&str, i32); N]) -> Self {
+        Self { cubes: lowercase_colors(cubes) }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
+#[allow(dead_code)]
 enum NotAGame {
     ImpossibleToSplitAtColon,
+    /// The part before the colon doesn't start with `Game`.
+    MissingGamePrefix,
     NoId,
+    InvalidSet(NotASet),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[allow(dead_code)]
+enum NotASet {
+    /// A comma separated entry that isn't a count followed by a color.
+    NotCountAndColor(String),
+    InvalidCount(ParseIntError),
+    NegativeCount(i32),
+    UnknownColor(String),
+    DuplicateColor(String),
 }
 
 impl FromStr for Game {
     type Err = NotAGame;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (id_part, set_part) = s.split_once(':').ok_or(NotAGame::ImpossibleToSplitAtColon)?;
-        let (_, id) = id_part.split_once(' ').ok_or(NotAGame::NoId)?;
-
-        let id = id.parse().map_err(|_| NotAGame::NoId)?;
+        Self::parse(s, None)
+    }
+}
 
-        let sets: Vec<Set> = set_part
-            .split(';')
-            .map(Set::from_str)
-            .collect::<Result<_, _>>()
-            .unwrap();
-            
+impl FromStr for Set {
+    type Err = NotASet;
 
-        Ok(Self {
-            id,
-            sets
-        })
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, None)
     }
 }
 
-impl FromStr for Set {
-    type Err = Infallible;
+/// Reads a bag written like a set, such as `12 red, 13 green, 14 blue`.
+impl FromStr for Bag {
+    type Err = NotASet;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let color_count: HashMap<Color, i32> = s
-            .split(',')
-            .filter_map(|count_and_color| {
-                count_and_color.trim().split_once(' ').map(|(count, color)| {
-                    (Color::from_str(color).unwrap(), count.parse().unwrap())
-                })
-            })
-            .collect();
-        
-        Ok(Self {
-            red: color_count.get(&Color::Red).copied().unwrap_or_default(),
-            green: color_count.get(&Color::Green).copied().unwrap_or_default(),
-            blue: color_count.get(&Color::Blue).copied().unwrap_or_default(),
-        })
+        let Set { cubes } = s.parse()?;
+        Ok(Self { cubes })
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{Game, Set, cube_conundrum, cube_conundrum_part_2};
+    use crate::{Game, Set, Bag, cube_conundrum, cube_conundrum_part_2, parse_games, parse_games_lenient, possible_games, LineError, NotAGame, NotASet, PUZZLE_COLORS};
     use test_case::test_case;
 
     const EXAMPLE_INPUT: &str = r"
@@ -161,7 +329,7 @@ mod tests {
 
         let result = cube_conundrum(EXAMPLE_INPUT);
 
-        assert_eq!(result, EXPECTED_OUTPUT);
+        assert_eq!(result, Ok(EXPECTED_OUTPUT));
     }
 
     #[test]
@@ -170,7 +338,7 @@ mod tests {
 
         let result = cube_conundrum_part_2(EXAMPLE_INPUT);
 
-        assert_eq!(result, EXPECTED_OUTPUT);
+        assert_eq!(result, Ok(EXPECTED_OUTPUT));
     }
 
     #[test_case(
@@ -178,21 +346,9 @@ mod tests {
         Game {
             id: 1,
             sets: vec![
-                Set {
-                    red: 4,
-                    green: 0,
-                    blue: 3,
-                },
-                Set {
-                    red: 1,
-                    green: 2,
-                    blue: 6,
-                },
-                Set {
-                    red: 0,
-                    green: 2,
-                    blue: 0,
-                },
+                Set::from([("red", 4), ("blue", 3)]),
+                Set::from([("red", 1), ("green", 2), ("blue", 6)]),
+                Set::from([("green", 2)]),
             ]
         }
     )]
@@ -201,4 +357,116 @@ mod tests {
         assert_eq!(result, Ok(expected_output));
     }
 
-}
\ No newline at end of file
+    #[test_case(
+        "Game 7: 2 Purple, 1 teal; 5 RED",
+        Game {
+            id: 7,
+            sets: vec![
+                Set::from([("purple", 2), ("teal", 1)]),
+                Set::from([("red", 5)]),
+            ]
+        }
+    )]
+    fn deserialize_game_with_any_colors(input_game: &str, expected_output: Game) {
+        let result = input_game.parse::<Game>();
+        assert_eq!(result, Ok(expected_output));
+    }
+
+    #[test]
+    fn possible_games_for_many_bags() {
+        let games = parse_games(EXAMPLE_INPUT, None).unwrap();
+        let bags = [
+            Bag::puzzle(),
+            Bag::from([("red", 20), ("green", 13), ("blue", 15)]),
+            Bag::from([("red", 4), ("green", 3), ("blue", 6)]),
+            Bag::from([("red", 100), ("green", 100)]),
+            Bag::default(),
+        ];
+
+        assert_eq!(possible_games(&games, &bags), vec![
+            vec![1, 2, 5],
+            vec![1, 2, 3, 4, 5],
+            vec![1, 2],
+            vec![],
+            vec![],
+        ]);
+    }
+
+    #[test]
+    fn custom_colors_need_matching_bag() {
+        let games = parse_games("Game 1: 3 purple, 1 red\nGame 2: 1 red", None).unwrap();
+
+        let bags = [Bag::puzzle(), Bag::from([("Purple", 3), ("red", 1)])];
+        assert_eq!(possible_games(&games, &bags), vec![vec![2], vec![1, 2]]);
+    }
+
+    #[test]
+    fn parse_bags_like_sets() {
+        assert_eq!("12 red, 13 Green, 14 blue".parse(), Ok(Bag::puzzle()));
+        assert_eq!("3 purple".parse(), Ok(Bag::from([("purple", 3)])));
+        assert_eq!("3 red, 1 red".parse::<Bag>(), Err(NotASet::DuplicateColor("red".to_owned())));
+    }
+
+    #[test]
+    fn minimum_bag_covers_every_color() {
+        let game: Game = "Game 1: 3 purple, 1 red; 5 purple; 2 teal".parse().unwrap();
+        assert_eq!(game.minimum_bag(), Bag::from([("purple", 5), ("red", 1), ("teal", 2)]));
+        assert_eq!(game.sets[0].count("purple"), 3);
+        assert_eq!(game.sets[0].count("teal"), 0);
+    }
+
+    #[test_case("Game 1 3 blue", NotAGame::ImpossibleToSplitAtColon)]
+    #[test_case("Foo 3: 3 blue", NotAGame::MissingGamePrefix)]
+    #[test_case("Games 3: 3 blue", NotAGame::MissingGamePrefix)]
+    #[test_case(": 3 blue", NotAGame::MissingGamePrefix)]
+    #[test_case("Game: 3 blue", NotAGame::NoId)]
+    #[test_case("Game x: 3 blue", NotAGame::NoId)]
+    #[test_case("Game -1: 3 blue", NotAGame::NoId)]
+    #[test_case("Game 1: 3 purple", NotAGame::InvalidSet(NotASet::UnknownColor("purple".to_owned())))]
+    #[test_case("Game 1: 3 blue, 2 Blue", NotAGame::InvalidSet(NotASet::DuplicateColor("blue".to_owned())))]
+    #[test_case("Game 1: 3 blue; -2 red", NotAGame::InvalidSet(NotASet::NegativeCount(-2)))]
+    #[test_case("Game 1: 3 blue; red", NotAGame::InvalidSet(NotASet::NotCountAndColor("red".to_owned())))]
+    #[test_case("Game 1: 3 blue;", NotAGame::InvalidSet(NotASet::NotCountAndColor("".to_owned())))]
+    fn strict_parse_errors(line: &str, expected: NotAGame) {
+        let result = Game::parse(line, Some(&PUZZLE_COLORS));
+        assert_eq!(result, Err(expected));
+    }
+
+    #[test]
+    fn invalid_count() {
+        let result = Game::parse("Game 1: three blue", Some(&PUZZLE_COLORS));
+        assert!(matches!(result, Err(NotAGame::InvalidSet(NotASet::InvalidCount(_)))));
+    }
+
+    #[test]
+    fn any_alphabetic_color_without_palette() {
+        assert!(Game::parse("Game 1: 3 purple", None).is_ok());
+        assert_eq!(
+            Game::parse("Game 1: 3 pur-ple", None),
+            Err(NotAGame::InvalidSet(NotASet::UnknownColor("pur-ple".to_owned()))),
+        );
+    }
+
+    #[test]
+    fn strict_mode_reports_every_bad_line() {
+        let input = "Game 1: 3 blue\n\nGame 2: 3 purple\nGame 3: 1 red\nGame x: 1 red";
+        let result = parse_games(input, Some(&PUZZLE_COLORS));
+
+        assert_eq!(result, Err(vec![
+            LineError { line: 3, error: NotAGame::InvalidSet(NotASet::UnknownColor("purple".to_owned())) },
+            LineError { line: 5, error: NotAGame::NoId },
+        ]));
+        assert!(cube_conundrum(input).is_err());
+    }
+
+    #[test]
+    fn lenient_mode_reports_skipped_lines() {
+        let input = "Game 1: 3 blue\nGame 2: 3 blue, 1 blue\nGame 3: 1 red";
+        let (games, skipped) = parse_games_lenient(input, Some(&PUZZLE_COLORS));
+
+        assert_eq!(games.iter().map(|game| game.id).collect::<Vec<_>>(), vec![1, 3]);
+        assert_eq!(skipped, vec![
+            LineError { line: 2, error: NotAGame::InvalidSet(NotASet::DuplicateColor("blue".to_owned())) },
+        ]);
+    }
+}
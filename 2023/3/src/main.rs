@@ -1,36 +1,80 @@
-use std::ops::Range;
+use std::{collections::HashMap, fmt, ops::Range};
 
-fn main() {
+fn main() -> Result<(), SchematicError> {
     const INPUT: &str = include_str!("../input.txt");
-    let result = gear_ratios_part_2(INPUT);
-    println!("Result: {result}");
+    let part_1 = gear_ratios(INPUT)?;
+    let part_2 = gear_ratios_part_2(INPUT)?;
+
+    if std::env::args().any(|arg| arg == "--explain") {
+        let table = SymbolTable::default();
+        let schematic = Schematic::parse_with(INPUT, &table)?;
+        for gear in schematic.gears(&GearRule::default())? {
+            println!("{gear}");
+        }
+        for category in table.category_names() {
+            println!("{category}: {} symbols", schematic.symbols_in_category(category).count());
+        }
+    }
+
+    println!("Part 1: {part_1}");
+    println!("Part 2: {part_2}");
+    Ok(())
 }
 
-fn gear_ratios(input: &str) -> u32 {
-    let (numbers, symbols) = parse_numbers_and_symbols(input);
-    get_numbers_adjacent_to_symbols(numbers, symbols).sum()
+fn gear_ratios(input: &str) -> Result<u64, SchematicError> {
+    let schematic = Schematic::parse(input)?;
+    Ok(get_numbers_adjacent_to_symbols(&schematic).map(u64::from).sum())
 }
 
-fn gear_ratios_part_2(input: &str) -> u32 {
-    let (numbers, symbols) = parse_numbers_and_symbols(input);
-    get_gear_ratios_sum(numbers, symbols)
+fn gear_ratios_part_2(input: &str) -> Result<u64, SchematicError> {
+    let schematic = Schematic::parse(input)?;
+    get_gear_ratios_sum(&schematic, &GearRule::default())
 }
 
-fn parse_numbers_and_symbols(input: &str) -> (Vec<Number>, Vec<Symbol>) {
+/// Lines are trimmed and leading or trailing blank lines are ignored, but every
+/// line in between must be exactly as wide as the first non-blank one, so a
+/// blank line inside the schematic is a ragged, zero-width row.
+fn parse_numbers_and_symbols(input: &str, table: &SymbolTable) -> Result<(Vec<Number>, Vec<Symbol>), SchematicError> {
     let mut numbers = Vec::new();
     let mut symbols = Vec::new();
+    let mut width = None;
+    let mut first_blank_row = None;
 
     for (row, line) in input.lines().enumerate() {
         let mut col_span_begin = None;
         let line = line.trim();
 
+        if line.is_empty() {
+            if width.is_some() {
+                first_blank_row.get_or_insert(row);
+            }
+            continue;
+        }
+
+        if let (Some(row), Some(expected)) = (first_blank_row, width) {
+            return Err(SchematicError::RaggedLine { row, expected, found: 0 });
+        }
+
+        if let Some((col, ch)) = line.chars().enumerate().find(|(_, c)| !c.is_ascii_graphic()) {
+            return Err(SchematicError::InvalidChar { row, col, ch });
+        }
+
+        // Only ASCII is left, so byte and char offsets agree from here on.
+        match width {
+            None => width = Some(line.len()),
+            Some(expected) if expected != line.len() => {
+                return Err(SchematicError::RaggedLine { row, expected, found: line.len() });
+            }
+            _ => {}
+        }
+
         for (col, c) in line.chars().enumerate() {
             match col_span_begin {
-                Some(col_begin) if !c.is_numeric() => {
-                    numbers.push(Number::from_span(line, row, col_begin..col));
+                Some(col_begin) if !c.is_ascii_digit() => {
+                    numbers.push(Number::from_span(line, row, col_begin..col)?);
                     col_span_begin = None;
                 },
-                None if c.is_numeric() => {
+                None if c.is_ascii_digit() => {
                     col_span_begin = Some(col);
                 }
                 _ => {}
@@ -38,7 +82,8 @@ fn parse_numbers_and_symbols(input: &str) -> (Vec<Number>, Vec<Symbol>) {
 
             if c.is_symbol() {
                 symbols.push(Symbol {
-                    ty: c.into(),
+                    ch: c,
+                    category: table.category_of(c).to_owned(),
                     pos: Position {
                         col,
                         row,
@@ -48,39 +93,219 @@ fn parse_numbers_and_symbols(input: &str) -> (Vec<Number>, Vec<Symbol>) {
         }
 
         if let Some(col_begin) = col_span_begin {
-            numbers.push(Number::from_span(line, row, col_begin..line.len()));
+            numbers.push(Number::from_span(line, row, col_begin..line.len())?);
         }
     }
 
-    (numbers, symbols)
+    Ok((numbers, symbols))
 }
 
-fn get_numbers_adjacent_to_symbols(numbers: Vec<Number>, symbols: Vec<Symbol>) -> impl Iterator<Item = u32> {
-    numbers
-        .into_iter()
-        .filter_map(move |number| {
-            symbols
-                .iter()
-                .any(|symbol| number.pos.is_adjacent(&symbol.pos))
-                .then_some(number.val)
-        })
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(dead_code)]
+enum SchematicError {
+    InvalidChar { row: usize, col: usize, ch: char },
+    RaggedLine { row: usize, expected: usize, found: usize },
+    NumberTooLarge(Position<Range<usize>>),
+    RatioOverflow(Position<usize>),
+    SumOverflow,
+}
+
+fn get_numbers_adjacent_to_symbols(schematic: &Schematic) -> impl Iterator<Item = u32> + '_ {
+    (0..schematic.numbers.len())
+        .filter(|&id| !schematic.symbols_touching_number(id).is_empty())
+        .map(|id| schematic.numbers[id].val)
 }
 
-fn get_gear_ratios_sum(numbers: Vec<Number>, symbols: Vec<Symbol>) -> u32 {
-    let gears = symbols
+fn get_gear_ratios_sum(schematic: &Schematic, rule: &GearRule) -> Result<u64, SchematicError> {
+    schematic
+        .gears(rule)?
         .into_iter()
-        .filter(|symbol| matches!(symbol.ty, Type::Gear));
+        .try_fold(0_u64, |acc, gear| acc.checked_add(gear.ratio))
+        .ok_or(SchematicError::SumOverflow)
+}
 
-    gears
-        .map(|gear| {
-            numbers
-                .iter()
-                .filter(|number| number.pos.is_adjacent(&gear.pos))
-                .fold((0, 1), |(acc_count, acc_ratio), number| (acc_count + 1, acc_ratio * number.val))
-        })
-        .filter(|(adjacency_count, _)| *adjacency_count > 1)
-        .map(|(_, gear_ratio)| gear_ratio)
-        .sum()
+/// A gear is a specific symbol touching exactly `neighbours` part numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct GearRule {
+    symbol: char,
+    neighbours: usize,
+}
+
+impl Default for GearRule {
+    /// The puzzle's gears: a `*` next to exactly two part numbers.
+    fn default() -> Self {
+        Self {
+            symbol: '*',
+            neighbours: 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct GearReport {
+    symbol: SymbolId,
+    pos: Position<usize>,
+    part_numbers: Vec<u32>,
+    ratio: u64,
+}
+
+impl fmt::Display for GearReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let part_numbers: Vec<String> = self.part_numbers.iter().map(u32::to_string).collect();
+        write!(
+            f,
+            "gear at row {}, col {}: {} = {}",
+            self.pos.row, self.pos.col, part_numbers.join(" * "), self.ratio,
+        )
+    }
+}
+
+/// Index of a number in [`Schematic::numbers`].
+type NumberId = usize;
+/// Index of a symbol in [`Schematic::symbols`].
+type SymbolId = usize;
+
+/// The numbers and symbols of an engine schematic, plus a grid telling which
+/// of them covers every cell, so adjacency is answered by looking at the
+/// handful of cells around something instead of comparing everything with
+/// everything.
+#[derive(Debug)]
+struct Schematic {
+    numbers: Vec<Number>,
+    symbols: Vec<Symbol>,
+    grid: Vec<Vec<Cell>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Cell {
+    #[default]
+    Empty,
+    Number(NumberId),
+    Symbol(SymbolId),
+}
+
+impl Schematic {
+    fn parse(input: &str) -> Result<Self, SchematicError> {
+        Self::parse_with(input, &SymbolTable::default())
+    }
+
+    fn parse_with(input: &str, table: &SymbolTable) -> Result<Self, SchematicError> {
+        let (numbers, symbols) = parse_numbers_and_symbols(input, table)?;
+        Ok(Self::new(numbers, symbols))
+    }
+
+    fn new(numbers: Vec<Number>, symbols: Vec<Symbol>) -> Self {
+        let mut grid: Vec<Vec<Cell>> = Vec::new();
+
+        let mut fill = |row: usize, cols: Range<usize>, cell: Cell| {
+            if grid.len() <= row {
+                grid.resize_with(row + 1, Vec::new);
+            }
+            let cells = &mut grid[row];
+            if cells.len() < cols.end {
+                cells.resize(cols.end, Cell::Empty);
+            }
+            cells[cols].fill(cell);
+        };
+
+        for (id, number) in numbers.iter().enumerate() {
+            fill(number.pos.row, number.pos.col.clone(), Cell::Number(id));
+        }
+
+        for (id, symbol) in symbols.iter().enumerate() {
+            fill(symbol.pos.row, symbol.pos.col..symbol.pos.col + 1, Cell::Symbol(id));
+        }
+
+        Self {
+            numbers,
+            symbols,
+            grid,
+        }
+    }
+
+    fn cell(&self, row: usize, col: usize) -> Cell {
+        self.grid
+            .get(row)
+            .and_then(|cells| cells.get(col))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Every distinct number touching the symbol, in the order they were
+    /// parsed.
+    fn numbers_touching_symbol(&self, id: SymbolId) -> Vec<NumberId> {
+        let pos = &self.symbols[id].pos;
+
+        let mut touching: Vec<NumberId> = surrounding_cells(pos.row, pos.col..pos.col + 1)
+            .filter_map(|(row, col)| match self.cell(row, col) {
+                Cell::Number(number_id) => Some(number_id),
+                _ => None,
+            })
+            .collect();
+
+        touching.sort_unstable();
+        touching.dedup();
+        touching
+    }
+
+    /// Every symbol matching the rule, along with its part numbers and ratio.
+    fn gears(&self, rule: &GearRule) -> Result<Vec<GearReport>, SchematicError> {
+        (0..self.symbols.len())
+            .filter(|&id| self.symbols[id].ch == rule.symbol)
+            .map(|id| (id, self.numbers_touching_symbol(id)))
+            .filter(|(_, adjacent_numbers)| adjacent_numbers.len() == rule.neighbours)
+            .map(|(id, adjacent_numbers)| {
+                let pos = self.symbols[id].pos.clone();
+
+                let part_numbers: Vec<u32> = adjacent_numbers
+                    .into_iter()
+                    .map(|number_id| self.numbers[number_id].val)
+                    .collect();
+
+                let ratio = part_numbers
+                    .iter()
+                    .try_fold(1_u64, |acc, &val| acc.checked_mul(val as u64))
+                    .ok_or_else(|| SchematicError::RatioOverflow(pos.clone()))?;
+
+                Ok(GearReport {
+                    symbol: id,
+                    pos,
+                    part_numbers,
+                    ratio,
+                })
+            })
+            .collect()
+    }
+
+    /// Every symbol sorted into `category`, in the order they were parsed.
+    fn symbols_in_category<'a>(&'a self, category: &'a str) -> impl Iterator<Item = SymbolId> + 'a {
+        (0..self.symbols.len()).filter(move |&id| self.symbols[id].category == category)
+    }
+
+    /// Every symbol touching the number, in the order they were parsed.
+    fn symbols_touching_number(&self, id: NumberId) -> Vec<SymbolId> {
+        let pos = &self.numbers[id].pos;
+
+        let mut touching: Vec<SymbolId> = surrounding_cells(pos.row, pos.col.clone())
+            .filter_map(|(row, col)| match self.cell(row, col) {
+                Cell::Symbol(symbol_id) => Some(symbol_id),
+                _ => None,
+            })
+            .collect();
+
+        touching.sort_unstable();
+        touching
+    }
+}
+
+/// The ring of cells around a horizontal span, diagonals included, leaving out
+/// whatever would fall off the top or left of the grid.
+fn surrounding_cells(row: usize, cols: Range<usize>) -> impl Iterator<Item = (usize, usize)> {
+    let rows = row.saturating_sub(1)..=row + 1;
+    let around = cols.start.saturating_sub(1)..cols.end + 1;
+
+    rows.flat_map(move |r| around.clone().map(move |c| (r, c)))
+        .filter(move |(r, c)| *r != row || !cols.contains(c))
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -90,80 +315,109 @@ struct Number {
 }
 
 impl Number {
-    fn from_span(line: &str, row: usize, col: Range<usize>) -> Self {
+    /// `line[col]` must be made of ASCII digits only, so the only way to
+    /// fail is a number too large for a `u32`.
+    fn from_span(line: &str, row: usize, col: Range<usize>) -> Result<Self, SchematicError> {
         let pos = Position {
             row,
             col,
         };
 
-        let val = line[pos.col.clone()].parse().unwrap();
+        let val = line[pos.col.clone()]
+            .parse()
+            .map_err(|_| SchematicError::NumberTooLarge(pos.clone()))?;
 
-        Self {
+        Ok(Self {
             val,
             pos,
-        }
+        })
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct Symbol {
-    ty: Type,
+    ch: char,
+    category: String,
     pos: Position<usize>
 }
 
+const DEFAULT_SYMBOL_CATEGORIES: &[(char, &str)] = &[
+    ('*', "gear"),
+    ('+', "operator"),
+    ('-', "operator"),
+    ('/', "operator"),
+    ('=', "operator"),
+    ('%', "operator"),
+    ('$', "currency"),
+];
+
+const DEFAULT_SYMBOL_CATEGORY: &str = "other";
+
+/// Sorts symbols into named categories. Symbols missing from the table fall
+/// into the fallback category.
 #[derive(Debug, Clone, PartialEq, Eq)]
-enum Type {
-    Gear,
-    SomethingElse,
+struct SymbolTable {
+    categories: HashMap<char, String>,
+    fallback: String,
 }
 
-impl From<char> for Type {
-    fn from(value: char) -> Self {
-        match value {
-            '*' => Self::Gear,
-            _ => Self::SomethingElse,
+impl SymbolTable {
+    fn new(categories: &[(char, &str)], fallback: &str) -> Self {
+        Self {
+            categories: categories
+                .iter()
+                .map(|&(symbol, category)| (symbol, category.to_owned()))
+                .collect(),
+            fallback: fallback.to_owned(),
         }
     }
-}
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct Position<T> {
-    row: usize,
-    col: T,
-}
+    /// Every category a symbol can fall into, fallback included, sorted by
+    /// name.
+    fn category_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.categories
+            .values()
+            .map(String::as_str)
+            .chain([self.fallback.as_str()])
+            .collect();
+
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
 
-impl Position<Range<usize>> {
-    fn is_adjacent(&self, to: &Position<usize>) -> bool {
-        let row_span = (to.row-1)..=(to.row+1);
-        row_span.contains(&self.row) && self.col.extend(1).contains(&(to.col as isize))
+    fn category_of(&self, symbol: char) -> &str {
+        self.categories
+            .get(&symbol)
+            .unwrap_or(&self.fallback)
     }
 }
 
-trait RangeExtension {
-    fn extend(&self, by: isize) -> Range<isize>;
+impl Default for SymbolTable {
+    fn default() -> Self {
+        Self::new(DEFAULT_SYMBOL_CATEGORIES, DEFAULT_SYMBOL_CATEGORY)
+    }
 }
 
-impl RangeExtension for Range<usize> {
-    fn extend(&self, by: isize) -> Range<isize> {
-        let start = self.start as isize;
-        let end = self.end as isize;
-        (start-by)..(end+by)
-    }
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Position<T> {
+    row: usize,
+    col: T,
 }
 
 trait IsSymbol {
-    fn is_symbol(self) -> bool;
+    fn is_symbol(&self) -> bool;
 }
 
 impl IsSymbol for char {
-    fn is_symbol(self) -> bool {
-        !self.is_numeric() && self != '.'
+    fn is_symbol(&self) -> bool {
+        !self.is_ascii_digit() && *self != '.'
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{gear_ratios, gear_ratios_part_2, Number, Symbol, Position, Type, parse_numbers_and_symbols};
+    use crate::{gear_ratios, gear_ratios_part_2, Number, Symbol, Position, parse_numbers_and_symbols, Schematic, GearRule, GearReport, SymbolTable, SchematicError};
     use test_case::test_case;
 
     #[test]
@@ -181,11 +435,11 @@ mod tests {
             .664.598..
         ";
 
-        const EXPECTED_OUTPUT: u32 = 4361;
+        const EXPECTED_OUTPUT: u64 = 4361;
 
         let result = gear_ratios(INPUT);
 
-        assert_eq!(result, EXPECTED_OUTPUT);
+        assert_eq!(result, Ok(EXPECTED_OUTPUT));
     }
 
 
@@ -204,11 +458,11 @@ mod tests {
             .664.598..
         ";
 
-        const EXPECTED_OUTPUT: u32 = 467835;
+        const EXPECTED_OUTPUT: u64 = 467835;
 
         let result = gear_ratios_part_2(INPUT);
 
-        assert_eq!(result, EXPECTED_OUTPUT);
+        assert_eq!(result, Ok(EXPECTED_OUTPUT));
     }
 
     #[test_case(
@@ -236,7 +490,8 @@ mod tests {
         vec![],
         vec![
             Symbol {
-                ty: Type::Gear,
+                ch: '*',
+                category: "gear".to_owned(),
                 pos: Position {
                     row: 1,
                     col: 3,
@@ -257,7 +512,8 @@ mod tests {
         ],
         vec![
             Symbol {
-                ty: Type::Gear,
+                ch: '*',
+                category: "gear".to_owned(),
                 pos: Position {
                     row: 4,
                     col: 3,
@@ -286,8 +542,197 @@ mod tests {
         vec![]
     )]
     fn test_parse_numbers_and_symbols(input: &str, expected_numbers: Vec<Number>, expected_symbols: Vec<Symbol>) {
-        let (numbers, symbols) = parse_numbers_and_symbols(input);
+        let (numbers, symbols) = parse_numbers_and_symbols(input, &SymbolTable::default()).unwrap();
         assert_eq!(numbers, expected_numbers);
         assert_eq!(symbols, expected_symbols);
     }
+
+    const EXAMPLE_INPUT: &str = r"
+467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..
+";
+
+    fn find_symbol(schematic: &Schematic, row: usize, col: usize) -> usize {
+        schematic.symbols
+            .iter()
+            .position(|symbol| symbol.pos == Position { row, col })
+            .unwrap()
+    }
+
+    fn find_number(schematic: &Schematic, val: u32) -> usize {
+        schematic.numbers
+            .iter()
+            .position(|number| number.val == val)
+            .unwrap()
+    }
+
+    #[test_case(2, 3, vec![467, 35])]
+    #[test_case(4, 6, vec![633])]
+    #[test_case(5, 3, vec![617])]
+    #[test_case(9, 5, vec![755, 598])]
+    fn test_numbers_touching_symbol(row: usize, col: usize, expected: Vec<u32>) {
+        let schematic = Schematic::parse(EXAMPLE_INPUT).unwrap();
+        let symbol = find_symbol(&schematic, row, col);
+
+        let touching: Vec<u32> = schematic
+            .numbers_touching_symbol(symbol)
+            .into_iter()
+            .map(|id| schematic.numbers[id].val)
+            .collect();
+
+        assert_eq!(touching, expected);
+    }
+
+    #[test_case(617, vec![(5, 3)])]
+    #[test_case(592, vec![(6, 5)])]
+    #[test_case(114, vec![])]
+    #[test_case(58, vec![])]
+    fn test_symbols_touching_number(val: u32, expected: Vec<(usize, usize)>) {
+        let schematic = Schematic::parse(EXAMPLE_INPUT).unwrap();
+        let number = find_number(&schematic, val);
+
+        let touching: Vec<(usize, usize)> = schematic
+            .symbols_touching_number(number)
+            .into_iter()
+            .map(|id| (schematic.symbols[id].pos.row, schematic.symbols[id].pos.col))
+            .collect();
+
+        assert_eq!(touching, expected);
+    }
+
+    #[test]
+    fn test_large_generated_schematic() {
+        const SIDE: usize = 600;
+
+        // A `12*34` motif repeated on every other line, so every `*` is a
+        // gear of exactly two numbers.
+        let line = "12*34.".repeat(SIDE / 6);
+        let blank = ".".repeat(SIDE);
+        let input = [line.as_str(), blank.as_str()].repeat(SIDE / 2).join("\n");
+
+        let gears = SIDE * SIDE / 12;
+        let result = gear_ratios_part_2(&input);
+
+        assert_eq!(result, Ok((gears as u64) * 12 * 34));
+        assert_eq!(gear_ratios(&input), Ok((gears as u64) * (12 + 34)));
+    }
+
+    #[test]
+    fn test_gear_report() {
+        let schematic = Schematic::parse(EXAMPLE_INPUT).unwrap();
+        let gears = schematic.gears(&GearRule::default()).unwrap();
+
+        assert_eq!(gears, vec![
+            GearReport {
+                symbol: find_symbol(&schematic, 2, 3),
+                pos: Position { row: 2, col: 3 },
+                part_numbers: vec![467, 35],
+                ratio: 16345,
+            },
+            GearReport {
+                symbol: find_symbol(&schematic, 9, 5),
+                pos: Position { row: 9, col: 5 },
+                part_numbers: vec![755, 598],
+                ratio: 451490,
+            },
+        ]);
+        assert_eq!(gears[0].to_string(), "gear at row 2, col 3: 467 * 35 = 16345");
+    }
+
+    #[test_case('*', 1, vec![617])]
+    #[test_case('*', 3, vec![])]
+    #[test_case('+', 1, vec![592])]
+    #[test_case('#', 1, vec![633])]
+    #[test_case('$', 1, vec![664])]
+    fn test_custom_gear_rules(symbol: char, neighbours: usize, expected_ratios: Vec<u64>) {
+        let schematic = Schematic::parse(EXAMPLE_INPUT).unwrap();
+        let ratios: Vec<u64> = schematic
+            .gears(&GearRule { symbol, neighbours })
+            .unwrap()
+            .into_iter()
+            .map(|gear| gear.ratio)
+            .collect();
+
+        assert_eq!(ratios, expected_ratios);
+    }
+
+    #[test]
+    fn test_gears_need_exact_neighbour_count() {
+        const INPUT: &str = "2.3\n.*.\n4..";
+        assert_eq!(gear_ratios_part_2(INPUT), Ok(0));
+
+        let schematic = Schematic::parse(INPUT).unwrap();
+        let rule = GearRule { symbol: '*', neighbours: 3 };
+        assert_eq!(schematic.gears(&rule).unwrap()[0].ratio, 24);
+    }
+
+    #[test]
+    fn test_symbol_categories() {
+        let table = SymbolTable::new(&[('*', "gear"), ('#', "hash"), ('$', "hash")], "misc");
+        let schematic = Schematic::parse_with(EXAMPLE_INPUT, &table).unwrap();
+
+        let positions = |category| -> Vec<(usize, usize)> {
+            schematic
+                .symbols_in_category(category)
+                .map(|id| (schematic.symbols[id].pos.row, schematic.symbols[id].pos.col))
+                .collect()
+        };
+
+        assert_eq!(positions("gear"), vec![(2, 3), (5, 3), (9, 5)]);
+        assert_eq!(positions("hash"), vec![(4, 6), (9, 3)]);
+        assert_eq!(positions("misc"), vec![(6, 5)]);
+        assert_eq!(table.category_names(), vec!["gear", "hash", "misc"]);
+        let schematic = Schematic::parse(EXAMPLE_INPUT).unwrap();
+        let currency = find_symbol(&schematic, 9, 3);
+        assert_eq!(schematic.symbols[currency].category, "currency");
+    }
+
+    #[test_case("*1.\n...\n...", 1, 0)]
+    #[test_case("...\n...\n.1*", 1, 0)]
+    #[test_case("1..\n*..\n...", 1, 0)]
+    #[test_case("...\n..*\n..1", 1, 0)]
+    #[test_case("1*2\n...\n...", 3, 2)]
+    #[test_case("...\n...\n3*4", 7, 12)]
+    #[test_case("5..\n*..\n6..", 11, 30)]
+    #[test_case("..7\n..*\n..8", 15, 56)]
+    #[test_case("*", 0, 0)]
+    #[test_case("9*9", 18, 81)]
+    #[test_case("9\n*\n9", 18, 81)]
+    #[test_case("", 0, 0)]
+    #[test_case("\n\n9*9\n\n\n", 18, 81)]
+    fn test_symbols_on_borders(input: &str, expected_part_1: u64, expected_part_2: u64) {
+        assert_eq!(gear_ratios(input), Ok(expected_part_1));
+        assert_eq!(gear_ratios_part_2(input), Ok(expected_part_2));
+    }
+
+    #[test_case("...\n.é.", SchematicError::InvalidChar { row: 1, col: 1, ch: 'é' })]
+    #[test_case("..\t..", SchematicError::InvalidChar { row: 0, col: 2, ch: '\t' })]
+    #[test_case(".1 2.", SchematicError::InvalidChar { row: 0, col: 2, ch: ' ' })]
+    #[test_case("...\n....", SchematicError::RaggedLine { row: 1, expected: 3, found: 4 })]
+    #[test_case("....\n\n...", SchematicError::RaggedLine { row: 1, expected: 4, found: 0 })]
+    #[test_case("\n....\n\n\n....\n", SchematicError::RaggedLine { row: 2, expected: 4, found: 0 })]
+    #[test_case(".99999999999*", SchematicError::NumberTooLarge(Position { row: 0, col: 1..12 }))]
+    fn test_invalid_schematics(input: &str, expected: SchematicError) {
+        assert_eq!(Schematic::parse(input).map(|_| ()), Err(expected.clone()));
+        assert_eq!(gear_ratios(input), Err(expected));
+    }
+
+    #[test]
+    fn test_ratio_overflow() {
+        const INPUT: &str = "4000000000.4000000000\n..........*..........\n4000000000.4000000000";
+        let schematic = Schematic::parse(INPUT).unwrap();
+        let rule = GearRule { symbol: '*', neighbours: 4 };
+        assert_eq!(
+            schematic.gears(&rule),
+            Err(SchematicError::RatioOverflow(Position { row: 1, col: 10 })),
+        );
+    }
 }
\ No newline at end of file
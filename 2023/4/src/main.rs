@@ -1,27 +1,58 @@
-use std::{collections::{HashSet, HashMap}, num::ParseIntError};
+use std::{collections::HashSet, num::ParseIntError, str::FromStr};
 
 fn main() -> Result<(), CardParseError> {
     const INPUT: &str = include_str!("../input.txt");
-    let result = scratchcards_part_2(INPUT)?;
-    println!("Result: {result}");
+    let part_1 = scratchcards(INPUT)?;
+    let part_2 = scratchcards_part_2(INPUT)?;
+    println!("Part 1: {part_1}");
+    println!("Part 2: {part_2}");
 
     Ok(())
 }
 
-fn scratchcards(input: &str) -> u32 {
-    input
-        .trim()
-        .lines()
-        .filter_map(calculate_points)
+fn scratchcards(input: &str) -> Result<u32, CardParseError> {
+    Ok(parse_scratchcards(input)?
+        .iter()
+        .map(Scratchcard::points)
         .sum()
+    )
 }
 
 fn scratchcards_part_2(input: &str) -> Result<u32, CardParseError> {
-    let mut largest_existing_id = 0;
-    let mut copis_of_cards = HashMap::<u32, u32>::new();
+    let cards = parse_scratchcards(input)?;
+
+    Ok(trace_cascade(&cards)
+        .into_iter()
+        .map(|trace| trace.copies)
+        .sum()
+    )
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Scratchcard {
+    id: u32,
+    winning: HashSet<u32>,
+    mine: HashSet<u32>,
+}
+
+impl Scratchcard {
+    fn matches(&self) -> u32 {
+        self.winning.intersection(&self.mine).count() as u32
+    }
+
+    fn points(&self) -> u32 {
+        match self.matches() {
+            0 => 0,
+            matches => 2_u32.pow(matches - 1),
+        }
+    }
+}
+
+impl FromStr for Scratchcard {
+    type Err = CardParseError;
 
-    for card_line in input.trim().lines().filter(|line| !line.is_empty()) {
-        let (id_part, numbers_part) = card_line
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (id_part, numbers_part) = s
             .trim()
             .split_once(':')
             .ok_or(CardParseError::MissingDelimiter(':'))?;
@@ -33,75 +64,111 @@ fn scratchcards_part_2(input: &str) -> Result<u32, CardParseError> {
             .parse()
             .map_err(|e| CardParseError::MissingId(e, id_part.to_owned()))?;
 
-        largest_existing_id = largest_existing_id.max(id);
-
         let (winning_numbers, my_numbers) = numbers_part
             .trim()
             .split_once('|')
             .ok_or(CardParseError::MissingDelimiter('|'))?;
 
-        let intersect_count = hits(winning_numbers, my_numbers);
-
-        let my_copies = *copis_of_cards.entry(id).or_insert(1);
-
-        for card_id_offset in 1..=intersect_count {
-            let card_incr_id = id + card_id_offset;
-            *copis_of_cards.entry(card_incr_id).or_insert(1) += my_copies;
-        }
+        Ok(Self {
+            id,
+            winning: parse_numbers(id, winning_numbers)?,
+            mine: parse_numbers(id, my_numbers)?,
+        })
     }
-
-    Ok(
-        copis_of_cards
-            .into_iter()
-            .filter(|(id, _)| *id <= largest_existing_id)
-            .map(|(_, count)| count)
-            .sum()
-    )
-
 }
 
 #[derive(Debug, PartialEq, Eq)]
+#[allow(dead_code)]
 enum CardParseError {
     MissingDelimiter(char),
     MissingPrefix,
     MissingId(ParseIntError, String),
+    InvalidNumber(ParseIntError, String),
+    DuplicateNumber { id: u32, number: u32 },
+    NonSequentialId { expected: u32, found: u32 },
 }
 
-fn calculate_points(line: &str) -> Option<u32> {
-    line
+/// Parses every card, making sure IDs count up from 1 without gaps so a
+/// card's position in the result is always `id - 1`.
+fn parse_scratchcards(input: &str) -> Result<Vec<Scratchcard>, CardParseError> {
+    input
         .trim()
-        .split_once(':')
-        .unwrap()
-        .1
-        .split_once('|')
-        .and_then(|(winning_numbers, my_numbers)| {
-        
-        let intersect_count = hits(winning_numbers, my_numbers);
-
-        (intersect_count >= 1)
-            .then_some(2_u32.pow((intersect_count as i32 - 1).max(0) as u32))
-    })
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .zip(1..)
+        .map(|(line, expected)| {
+            let card: Scratchcard = line.parse()?;
+            if card.id != expected {
+                return Err(CardParseError::NonSequentialId { expected, found: card.id });
+            }
+            Ok(card)
+        })
+        .collect()
 }
 
-fn hits(winning_numbers: &str, my_numbers: &str) -> u32 {
-    let winning_numbers = parse_numbers(winning_numbers);
-    let my_numbers = parse_numbers(my_numbers);
+fn parse_numbers(id: u32, numbers: &str) -> Result<HashSet<u32>, CardParseError> {
+    let mut parsed = HashSet::new();
+
+    for number in numbers.split_whitespace() {
+        let number = number
+            .parse()
+            .map_err(|e| CardParseError::InvalidNumber(e, number.to_owned()))?;
+
+        if !parsed.insert(number) {
+            return Err(CardParseError::DuplicateNumber { id, number });
+        }
+    }
+
+    Ok(parsed)
+}
 
-    winning_numbers
-        .intersection(&my_numbers).count() as u32
+/// What happened to a single card while copies cascaded down the pile.
+#[derive(Debug, PartialEq, Eq)]
+struct CardTrace {
+    id: u32,
+    matches: u32,
+    /// Copies won from earlier cards, not counting the original.
+    received: u32,
+    /// Every instance of the card, original included.
+    copies: u32,
+    /// Copies this card handed out to the cards below it, across all of its
+    /// instances.
+    produced: u32,
 }
 
-fn parse_numbers(numbers: &str) -> HashSet<u32> {
-    numbers
-        .split_whitespace()
-        .map(|number| number.parse())
-        .collect::<Result<_, _>>()
-        .unwrap()
+/// Expects cards as returned by [`parse_scratchcards`]. Copies of cards past
+/// the end of the pile are never handed out.
+fn trace_cascade(cards: &[Scratchcard]) -> Vec<CardTrace> {
+    let mut received = vec![0; cards.len()];
+
+    cards
+        .iter()
+        .enumerate()
+        .map(|(idx, card)| {
+            let matches = card.matches();
+            let copies = received[idx] + 1;
+
+            let won = (idx + 1..cards.len()).take(matches as usize);
+            let won_count = won.len() as u32;
+
+            for won_idx in won {
+                received[won_idx] += copies;
+            }
+
+            CardTrace {
+                id: card.id,
+                matches,
+                received: received[idx],
+                copies,
+                produced: copies * won_count,
+            }
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{scratchcards, calculate_points, scratchcards_part_2};
+    use crate::{scratchcards, scratchcards_part_2, parse_scratchcards, trace_cascade, CardParseError, CardTrace, Scratchcard};
     use test_case::test_case;
 
     const EXAMPLE_INPUT: &str = r"
@@ -119,7 +186,7 @@ mod tests {
 
         let result = scratchcards(EXAMPLE_INPUT);
 
-        assert_eq!(result, EXPECTED_OUTPUT);
+        assert_eq!(result, Ok(EXPECTED_OUTPUT));
     }
 
     #[test]
@@ -131,9 +198,55 @@ mod tests {
         assert_eq!(result, Ok(EXPECTED_OUTPUT));
     }
 
-    #[test_case("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53", Some(8))]
-    fn test_calculate_points(card: &str, expected_points: Option<u32>) {
-        let result = calculate_points(card);
+    #[test_case("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53", 8)]
+    #[test_case("Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36", 0)]
+    fn test_calculate_points(card: &str, expected_points: u32) {
+        let result = card.parse::<Scratchcard>().unwrap().points();
         assert_eq!(result, expected_points);
     }
+
+    #[test]
+    fn example_cascade_trace() {
+        let cards = parse_scratchcards(EXAMPLE_INPUT).unwrap();
+        let trace = trace_cascade(&cards);
+
+        let trace_of = |id, matches, received, copies, produced| CardTrace {
+            id, matches, received, copies, produced,
+        };
+
+        assert_eq!(trace, vec![
+            trace_of(1, 4, 0, 1, 4),
+            trace_of(2, 2, 1, 2, 4),
+            trace_of(3, 2, 3, 4, 8),
+            trace_of(4, 1, 7, 8, 8),
+            trace_of(5, 0, 13, 14, 0),
+            trace_of(6, 0, 0, 1, 0),
+        ]);
+    }
+
+    #[test]
+    fn copies_past_the_last_card_are_dropped() {
+        let cards = parse_scratchcards("Card 1: 1 2 | 1 2").unwrap();
+        let trace = trace_cascade(&cards);
+        assert_eq!(trace[0].produced, 0);
+        assert_eq!(scratchcards_part_2("Card 1: 1 2 | 1 2"), Ok(1));
+    }
+
+    #[test_case("Card 1: 1 2 1 | 3 4", CardParseError::DuplicateNumber { id: 1, number: 1 })]
+    #[test_case("Card 1: 1 2 | 3 4 3", CardParseError::DuplicateNumber { id: 1, number: 3 })]
+    #[test_case("Card 2: 1 2 | 3 4", CardParseError::NonSequentialId { expected: 1, found: 2 })]
+    #[test_case("Card 1: 1 | 2\nCard 3: 1 | 2", CardParseError::NonSequentialId { expected: 2, found: 3 })]
+    #[test_case("Card 1: 1 | 2\nCard 1: 1 | 2", CardParseError::NonSequentialId { expected: 2, found: 1 })]
+    #[test_case("Card 1 1 2 | 3 4", CardParseError::MissingDelimiter(':'))]
+    #[test_case("Card 1: 1 2 3 4", CardParseError::MissingDelimiter('|'))]
+    fn invalid_scratchcards(input: &str, expected: CardParseError) {
+        assert_eq!(parse_scratchcards(input), Err(expected));
+    }
+
+    #[test]
+    fn both_parts_report_parse_errors() {
+        const INPUT: &str = "Card 1: 41 x | 83";
+        assert!(matches!(scratchcards(INPUT), Err(CardParseError::InvalidNumber(_, _))));
+        assert!(matches!(scratchcards_part_2(INPUT), Err(CardParseError::InvalidNumber(_, _))));
+    }
 }
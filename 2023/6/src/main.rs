@@ -1,107 +1,158 @@
-use std::str::FromStr;
-
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
-fn main() {
+use physics::{Model, RaceModel};
+
+mod physics;
+
+fn main() -> Result<(), RaceSheetError> {
     const INPUT: &str = include_str!("../input.txt");
-    let result = calculate_numbers_of_ways_to_beat_the_only_race(INPUT);
-    println!("Result: {result}");
+
+    let model = match std::env::args().skip_while(|arg| arg != "--model").nth(1) {
+        Some(model) => model.parse().unwrap_or_else(|physics::InvalidModel(model)| {
+            eprintln!("Invalid --model `{model}`, expected constant[:<rate>], speed-capped:<max_speed> or friction:<deceleration>");
+            std::process::exit(2);
+        }),
+        None => Model::default(),
+    };
+
+    let part_1 = calculate_numbers_of_ways_to_beat_races(&model, INPUT)?;
+    let part_2 = calculate_numbers_of_ways_to_beat_the_only_race(&model, INPUT)?;
+    println!("Part 1: {part_1}");
+    println!("Part 2: {part_2}");
+    Ok(())
 }
 
-fn calculate_numbers_of_ways_to_beat_the_only_race(input: &str) -> u64 {
-    let (time_str, distance_str) = input.trim().split_once('\n').unwrap();
-    
-    let time: u64 = time_str
-        .trim()
-        .strip_prefix("Time:")
-        .unwrap()
-        .split_whitespace()
-        .fold(String::new(), |mut acc, val| {
-            acc.push_str(val);
-            acc
-        })
-        .parse()
-        .unwrap();
-
-    let distance: u64 = distance_str
-        .trim()
-        .strip_prefix("Distance:")
-        .unwrap()
-        .split_whitespace()
-        .fold(String::new(), |mut acc, val| {
-            acc.push_str(val);
-            acc
-        })
-        .parse()
-        .unwrap();
-
-    amount_of_ways_to_win_boat_race((time, distance))
+fn calculate_numbers_of_ways_to_beat_the_only_race<M: RaceModel + ?Sized>(
+    model: &M,
+    input: &str,
+) -> Result<u64, RaceSheetError> {
+    let race = parse_race_sheet(input)?.single_race()?;
+    Ok(amount_of_ways_to_win_boat_race(model, race))
 }
 
-fn calculate_numbers_of_ways_to_beat_races(input: &str) -> u64 {
-    let times_and_distances = parse_times_and_distances(input);
+fn calculate_numbers_of_ways_to_beat_races<M: RaceModel + Sync>(
+    model: &M,
+    input: &str,
+) -> Result<u64, RaceSheetError> {
+    let times_and_distances = parse_race_sheet(input)?.races()?;
 
-    times_and_distances
+    Ok(times_and_distances
         .into_par_iter()
-        .map(amount_of_ways_to_win_boat_race)
+        .map(|race| amount_of_ways_to_win_boat_race(model, race))
         .product()
+    )
 }
 
-fn amount_of_ways_to_win_boat_race((max_time, max_dist): (u64, u64)) -> u64 {
-    let [min, max] = solve_boat_equation(max_time, max_dist);
-    max - min + 1
+fn amount_of_ways_to_win_boat_race<M: RaceModel + ?Sized>(
+    model: &M,
+    (max_time, max_dist): (u64, u64),
+) -> u64 {
+    match model.winning_hold_times(max_time, max_dist) {
+        Some([min, max]) => max - min + 1,
+        None => 0,
+    }
 }
 
-fn parse_times_and_distances(input: &str) -> Vec<(u64, u64)> {
-    let (time_str, distance_str) = input.trim().split_once('\n').unwrap();
-    
-    let times = time_str
-        .trim()
-        .strip_prefix("Time:")
-        .unwrap()
-        .split_whitespace()
-        .map(|val| val.parse().unwrap());
+/// The raw columns of a race sheet. Both parts read the same sheet and only
+/// disagree on whether the columns are separate races or the digits of one.
+#[derive(Debug, PartialEq, Eq)]
+struct RaceSheet<'a> {
+    times: Vec<&'a str>,
+    distances: Vec<&'a str>,
+}
 
-    let distances = distance_str
-        .trim()
-        .strip_prefix("Distance:")
-        .unwrap()
-        .split_whitespace()
-        .map(|val| val.parse().unwrap());
+impl RaceSheet<'_> {
+    fn races(&self) -> Result<Vec<(u64, u64)>, RaceSheetError> {
+        self.times
+            .iter()
+            .zip(&self.distances)
+            .map(|(time, distance)| Ok((
+                parse_digits(time, Field::Time)?,
+                parse_digits(distance, Field::Distance)?,
+            )))
+            .collect()
+    }
+
+    /// Reads each line as a single number, ignoring the spaces between its
+    /// columns.
+    fn single_race(&self) -> Result<(u64, u64), RaceSheetError> {
+        Ok((
+            parse_digits(&self.times.concat(), Field::Time)?,
+            parse_digits(&self.distances.concat(), Field::Distance)?,
+        ))
+    }
+}
 
-    times
-        .zip(distances)
-        .collect()
+/// Only ever called on validated columns, so overflowing is the one way left
+/// for this to fail.
+fn parse_digits(digits: &str, field: Field) -> Result<u64, RaceSheetError> {
+    digits.parse().map_err(|_| RaceSheetError::Overflow(field))
 }
 
-fn solve_boat_equation(max_time: u64, max_distance: u64) -> [u64; 2] {
-    let a = - (ACCELERATION_RATE as f64);
-    let b = max_time as f64;
-    let c = - (max_distance as f64);
+fn parse_race_sheet(input: &str) -> Result<RaceSheet<'_>, RaceSheetError> {
+    let mut lines = input.trim().lines().map(str::trim);
 
-    let [lo, hi] = solve_second_degree_equation(a, b, c);
+    let times = parse_race_sheet_line(lines.next(), Field::Time)?;
+    let distances = parse_race_sheet_line(lines.next(), Field::Distance)?;
+
+    if times.len() != distances.len() {
+        return Err(RaceSheetError::CountMismatch {
+            times: times.len(),
+            distances: distances.len(),
+        });
+    }
 
-    [
-        lo.floor() as u64 + 1,
-        hi.ceil() as u64 - 1,
-    ]
+    Ok(RaceSheet { times, distances })
 }
 
-fn solve_second_degree_equation(a: f64, b: f64, c: f64) -> [f64; 2] {
-    let a2 = a * 2_f64;
-    let common = (b.powf(2_f64) - 4_f64 * a * c).sqrt();
+fn parse_race_sheet_line(line: Option<&str>, field: Field) -> Result<Vec<&str>, RaceSheetError> {
+    let values = line
+        .and_then(|line| line.strip_prefix(field.header()))
+        .ok_or(RaceSheetError::MissingHeader(field))?
+        .split_whitespace()
+        .collect::<Vec<_>>();
+
+    if values.is_empty() {
+        return Err(RaceSheetError::NoValues(field));
+    }
+
+    if let Some(value) = values.iter().find(|val| !val.bytes().all(|b| b.is_ascii_digit())) {
+        return Err(RaceSheetError::InvalidNumber(field, value.to_string()));
+    }
+
+    Ok(values)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Time,
+    Distance,
+}
 
-    [
-        (-b + common) / a2,
-        (-b - common) / a2
-    ]
+impl Field {
+    fn header(&self) -> &'static str {
+        match self {
+            Field::Time => "Time:",
+            Field::Distance => "Distance:",
+        }
+    }
 }
 
-const ACCELERATION_RATE: u64 = 1; // 1 millimeter / (1 millisecond ^ 2)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(dead_code)]
+enum RaceSheetError {
+    MissingHeader(Field),
+    NoValues(Field),
+    InvalidNumber(Field, String),
+    CountMismatch { times: usize, distances: usize },
+    Overflow(Field),
+}
 
 #[cfg(test)]
 mod tests {
-    use crate::{calculate_numbers_of_ways_to_beat_races, solve_boat_equation, calculate_numbers_of_ways_to_beat_the_only_race};
+    use crate::{calculate_numbers_of_ways_to_beat_races, calculate_numbers_of_ways_to_beat_the_only_race, amount_of_ways_to_win_boat_race, parse_race_sheet, Field, RaceSheetError};
+    use crate::physics::{ConstantAcceleration, Friction, InvalidModel, Model, RaceModel, SpeedCapped, ACCELERATION_RATE};
+    use proptest::prelude::*;
     use test_case::test_case;
 
     const EXAMPLE_INPUT: &str = r"
@@ -112,22 +163,199 @@ mod tests {
     #[test]
     fn example_case() {
         const EXPECTED_OUTPUT: u64 = 288;
-        let result = calculate_numbers_of_ways_to_beat_races(EXAMPLE_INPUT);
-        assert_eq!(result, EXPECTED_OUTPUT);
+        let result = calculate_numbers_of_ways_to_beat_races(&ConstantAcceleration::default(), EXAMPLE_INPUT);
+        assert_eq!(result, Ok(EXPECTED_OUTPUT));
     }
 
     #[test]
     fn example_case_part_2() {
         const EXPECTED_OUTPUT: u64 = 71503;
-        let result = calculate_numbers_of_ways_to_beat_the_only_race(EXAMPLE_INPUT);
-        assert_eq!(result, EXPECTED_OUTPUT);
+        let result = calculate_numbers_of_ways_to_beat_the_only_race(&ConstantAcceleration::default(), EXAMPLE_INPUT);
+        assert_eq!(result, Ok(EXPECTED_OUTPUT));
     }
 
-    #[test_case(7, 9, [2, 5])]
-    #[test_case(15, 40, [4, 11])]
-    #[test_case(30, 200, [11, 19])]
-    fn boat_eq(max_time: u64, max_distance: u64, expected_solutions: [u64; 2]) {
-        let result = solve_boat_equation(max_time, max_distance);
+    #[test_case(7, 9, Some([2, 5]))]
+    #[test_case(15, 40, Some([4, 11]))]
+    #[test_case(30, 200, Some([11, 19]))]
+    #[test_case(4, 4, None)]
+    #[test_case(4, 3, Some([2, 2]))]
+    #[test_case(0, 0, None)]
+    #[test_case(u64::MAX, u64::MAX, Some([2, u64::MAX - 2]))]
+    #[test_case(u64::MAX, 0, Some([1, u64::MAX - 1]))]
+    fn boat_eq(max_time: u64, max_distance: u64, expected_solutions: Option<[u64; 2]>) {
+        let result = ConstantAcceleration::default().winning_hold_times(max_time, max_distance);
         assert_eq!(result, expected_solutions);
     }
+
+    fn distance(max_time: u64, hold: u64) -> u128 {
+        ACCELERATION_RATE as u128 * hold as u128 * (max_time - hold) as u128
+    }
+
+    fn brute_force_ways(max_time: u64, max_distance: u64) -> u64 {
+        brute_force_ways_with(&ConstantAcceleration::default(), max_time, max_distance)
+    }
+
+    fn brute_force_ways_with(model: &dyn RaceModel, max_time: u64, max_distance: u64) -> u64 {
+        (0..=max_time)
+            .filter(|&hold| model.distance(hold, max_time) > max_distance as u128)
+            .count() as u64
+    }
+
+    #[test]
+    fn faster_acceleration_wins_more_races() {
+        let model = ConstantAcceleration { rate: 2 };
+        let result = calculate_numbers_of_ways_to_beat_races(&model, EXAMPLE_INPUT);
+        assert_eq!(result, Ok(6 * 12 * 23));
+    }
+
+    #[test_case("Distance: 9\nTime: 7", RaceSheetError::MissingHeader(Field::Time))]
+    #[test_case("Time: 7", RaceSheetError::MissingHeader(Field::Distance))]
+    #[test_case("Time: 7\nDist: 9", RaceSheetError::MissingHeader(Field::Distance))]
+    #[test_case("Time:\nDistance: 9", RaceSheetError::NoValues(Field::Time))]
+    #[test_case("Time: 7 15\nDistance: 9", RaceSheetError::CountMismatch { times: 2, distances: 1 })]
+    fn malformed_race_sheets(input: &str, expected: RaceSheetError) {
+        assert_eq!(parse_race_sheet(input), Err(expected));
+    }
+
+    #[test_case("Time: 7 1x\nDistance: 9 40", Field::Time, "1x")]
+    #[test_case("Time: 7 15\nDistance: 9 -40", Field::Distance, "-40")]
+    #[test_case("Time: 7 +15\nDistance: 9 40", Field::Time, "+15")]
+    fn invalid_numbers_fail_both_parts(input: &str, field: Field, value: &str) {
+        let expected = RaceSheetError::InvalidNumber(field, value.to_string());
+        let model = ConstantAcceleration::default();
+        assert_eq!(calculate_numbers_of_ways_to_beat_races(&model, input), Err(expected.clone()));
+        assert_eq!(calculate_numbers_of_ways_to_beat_the_only_race(&model, input), Err(expected));
+    }
+
+    #[test]
+    fn single_race_overflow() {
+        const INPUT: &str = "Time: 18446744073 709551615\nDistance: 18446744073 709551616";
+        let sheet = parse_race_sheet(INPUT).unwrap();
+        assert_eq!(sheet.single_race(), Err(RaceSheetError::Overflow(Field::Distance)));
+        assert_eq!(sheet.races(), Ok(vec![(18446744073, 18446744073), (709551615, 709551616)]));
+    }
+
+    #[test_case(10, 3, 3, 21)]
+    #[test_case(10, 3, 5, 15)]
+    #[test_case(10, 0, 3, 0)]
+    #[test_case(10, 10, 1, 9)]
+    fn speed_cap_distances(race_time: u64, max_speed: u64, hold_time: u64, expected: u128) {
+        let model = SpeedCapped { rate: 1, max_speed };
+        assert_eq!(model.distance(hold_time, race_time), expected);
+    }
+
+    #[test_case(10, 1, 4, 4 + 3 + 2 + 1)]
+    #[test_case(10, 2, 4, 4 + 2)]
+    #[test_case(10, 1, 8, 8 + 7)]
+    #[test_case(10, 0, 4, 24)]
+    #[test_case(10, 3, 10, 0)]
+    fn friction_distances(race_time: u64, deceleration: u64, hold_time: u64, expected: u128) {
+        let model = Friction { rate: 1, deceleration };
+        assert_eq!(model.distance(hold_time, race_time), expected);
+    }
+
+    #[test_case("constant", Model::ConstantAcceleration(ConstantAcceleration { rate: ACCELERATION_RATE }))]
+    #[test_case("constant:3", Model::ConstantAcceleration(ConstantAcceleration { rate: 3 }))]
+    #[test_case("speed-capped:10", Model::SpeedCapped(SpeedCapped { rate: ACCELERATION_RATE, max_speed: 10 }))]
+    #[test_case("friction:2", Model::Friction(Friction { rate: ACCELERATION_RATE, deceleration: 2 }))]
+    fn parse_models(input: &str, expected: Model) {
+        assert_eq!(input.parse(), Ok(expected));
+    }
+
+    #[test_case("")]
+    #[test_case("speed-capped")]
+    #[test_case("friction:")]
+    #[test_case("friction:-1")]
+    #[test_case("gravity:3")]
+    fn invalid_models(input: &str) {
+        assert_eq!(input.parse::<Model>(), Err(InvalidModel(input.to_string())));
+    }
+
+    #[test]
+    fn unrepresentable_distances_saturate() {
+        assert_eq!(ConstantAcceleration { rate: 5 }.distance(1 << 63, u64::MAX), u128::MAX);
+        assert_eq!(Friction { rate: u64::MAX, deceleration: 1 }.distance(1 << 63, u64::MAX), u128::MAX);
+    }
+
+    #[test]
+    fn friction_beats_records_at_u64_max() {
+        let model = Friction { rate: u64::MAX, deceleration: 1 };
+        assert_eq!(model.winning_hold_times(u64::MAX, 0), Some([1, u64::MAX - 1]));
+        assert_eq!(model.winning_hold_times(u64::MAX, u64::MAX), Some([1, u64::MAX - 1]));
+    }
+
+    proptest! {
+        #[test]
+        fn speed_capped_matches_brute_force(
+            max_time in 0_u64..500,
+            max_distance in 0_u64..20_000,
+            rate in 0_u64..5,
+            max_speed in 0_u64..300,
+        ) {
+            let model = SpeedCapped { rate, max_speed };
+            prop_assert_eq!(
+                amount_of_ways_to_win_boat_race(&model, (max_time, max_distance)),
+                brute_force_ways_with(&model, max_time, max_distance),
+            );
+        }
+
+        #[test]
+        fn friction_matches_brute_force(
+            max_time in 0_u64..500,
+            max_distance in 0_u64..20_000,
+            rate in 0_u64..5,
+            deceleration in 0_u64..20,
+        ) {
+            let model = Friction { rate, deceleration };
+            prop_assert_eq!(
+                amount_of_ways_to_win_boat_race(&model, (max_time, max_distance)),
+                brute_force_ways_with(&model, max_time, max_distance),
+            );
+        }
+
+        #[test]
+        fn constant_acceleration_matches_numeric_solver(
+            max_time in 0_u64..2_000,
+            max_distance in 0_u64..1_000_000,
+            rate in 0_u64..5,
+        ) {
+            let model = ConstantAcceleration { rate };
+            let speed_capped = SpeedCapped { rate, max_speed: u64::MAX };
+            prop_assert_eq!(
+                model.winning_hold_times(max_time, max_distance),
+                speed_capped.winning_hold_times(max_time, max_distance),
+            );
+        }
+
+        #[test]
+        fn matches_brute_force(max_time in 0_u64..2_000, max_distance in 0_u64..1_000_000) {
+            prop_assert_eq!(
+                amount_of_ways_to_win_boat_race(&ConstantAcceleration::default(), (max_time, max_distance)),
+                brute_force_ways(max_time, max_distance),
+            );
+        }
+
+        #[test]
+        fn exact_when_record_is_reachable(max_time in 0_u64..2_000, hold in 0_u64..2_000) {
+            let hold = hold.min(max_time);
+            let record = distance(max_time, hold) as u64;
+            prop_assert_eq!(
+                amount_of_ways_to_win_boat_race(&ConstantAcceleration::default(), (max_time, record)),
+                brute_force_ways(max_time, record),
+            );
+        }
+
+        #[test]
+        fn boundaries_are_exact_for_any_u64(max_time in any::<u64>(), max_distance in any::<u64>()) {
+            let beats = |hold: u64| distance(max_time, hold) > max_distance as u128;
+            match ConstantAcceleration::default().winning_hold_times(max_time, max_distance) {
+                Some([lo, hi]) => {
+                    prop_assert!(beats(lo) && beats(hi));
+                    prop_assert!(lo == 0 || !beats(lo - 1));
+                    prop_assert!(hi == max_time || !beats(hi + 1));
+                }
+                None => prop_assert!(!beats(max_time / 2)),
+            }
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,236 @@
+use std::str::FromStr;
+
+/// How a boat turns a hold time into a travelled distance.
+///
+/// Every model follows the puzzle's shape: the button is held for some time,
+/// then the boat moves for the rest of the race. Models only need to provide
+/// [`RaceModel::distance`]; the winning hold times are then found numerically,
+/// which assumes the distance never decreases before its peak and never
+/// increases after it. Models with a closed form should override
+/// [`RaceModel::winning_hold_times`].
+pub trait RaceModel {
+    /// Distance covered in a race lasting `race_time` after holding the button
+    /// for `hold_time`, which is never longer than `race_time`.
+    fn distance(&self, hold_time: u64, race_time: u64) -> u128;
+
+    /// Returns the shortest and longest hold times that travel strictly
+    /// further than `record`, or `None` if the record can't be beaten.
+    fn winning_hold_times(&self, race_time: u64, record: u64) -> Option<[u64; 2]> {
+        solve_numerically(self, race_time, record)
+    }
+}
+
+/// The puzzle's rules: speed grows by `rate` for every millisecond the button
+/// is held and stays constant afterwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstantAcceleration {
+    pub rate: u64,
+}
+
+pub const ACCELERATION_RATE: u64 = 1; // 1 millimeter / (1 millisecond ^ 2)
+
+impl Default for ConstantAcceleration {
+    fn default() -> Self {
+        Self { rate: ACCELERATION_RATE }
+    }
+}
+
+impl RaceModel for ConstantAcceleration {
+    /// Saturates at `u128::MAX`, which still beats any `u64` record.
+    fn distance(&self, hold_time: u64, race_time: u64) -> u128 {
+        (self.rate as u128 * hold_time as u128).saturating_mul((race_time - hold_time) as u128)
+    }
+
+    /// Holding for `h` covers `rate * h * (race_time - h)`, so we need the
+    /// integer roots of `h² - race_time * h + k ≤ 0`, where `k` is the
+    /// smallest integer product that beats the record. Everything runs on
+    /// `u128`, which holds `race_time²` for any `u64`, and the integer square
+    /// root only gives an estimate that is nudged onto the exact boundary
+    /// afterwards.
+    fn winning_hold_times(&self, race_time: u64, record: u64) -> Option<[u64; 2]> {
+        let time = race_time as u128;
+        let target = (record.checked_div(self.rate)?) as u128 + 1;
+
+        let discriminant = (time * time).checked_sub(4 * target)?;
+        let beats_record = |hold: u128| hold * (time - hold) >= target;
+
+        let mut lo = (time - discriminant.isqrt()) / 2;
+        while !beats_record(lo) {
+            lo += 1;
+        }
+        while lo > 0 && beats_record(lo - 1) {
+            lo -= 1;
+        }
+
+        // Distances are symmetric around `race_time / 2`.
+        let hi = time - lo;
+        if lo > hi {
+            return None;
+        }
+
+        Some([lo as u64, hi as u64])
+    }
+}
+
+/// Like [`ConstantAcceleration`], but the boat can't go faster than
+/// `max_speed` no matter how long the button is held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpeedCapped {
+    pub rate: u64,
+    pub max_speed: u64,
+}
+
+impl RaceModel for SpeedCapped {
+    fn distance(&self, hold_time: u64, race_time: u64) -> u128 {
+        let speed = (self.rate as u128 * hold_time as u128).min(self.max_speed as u128);
+        speed * (race_time - hold_time) as u128
+    }
+}
+
+/// Like [`ConstantAcceleration`], but once released the boat loses
+/// `deceleration` speed at the end of every millisecond until it stops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Friction {
+    pub rate: u64,
+    pub deceleration: u64,
+}
+
+impl RaceModel for Friction {
+    /// Saturates at `u128::MAX` like [`ConstantAcceleration`]; the boat covers
+    /// at least half of `moving_time * speed`, so that is past any record too.
+    fn distance(&self, hold_time: u64, race_time: u64) -> u128 {
+        let speed = self.rate as u128 * hold_time as u128;
+        let coasting_time = (race_time - hold_time) as u128;
+
+        if self.deceleration == 0 {
+            return speed * coasting_time;
+        }
+
+        let deceleration = self.deceleration as u128;
+        let moving_time = coasting_time.min(speed.div_ceil(deceleration));
+
+        // speed + (speed - d) + (speed - 2d) + ... over `moving_time` steps.
+        // `lost` is below half of `moving_time * speed`, so it fits whenever that does.
+        let Some(top_speed_distance) = moving_time.checked_mul(speed) else {
+            return u128::MAX;
+        };
+        let lost = deceleration * (moving_time * moving_time.saturating_sub(1) / 2);
+        top_speed_distance - lost
+    }
+}
+
+/// One of the models above, as picked with `--model` on the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Model {
+    ConstantAcceleration(ConstantAcceleration),
+    SpeedCapped(SpeedCapped),
+    Friction(Friction),
+}
+
+impl Default for Model {
+    fn default() -> Self {
+        Self::ConstantAcceleration(ConstantAcceleration::default())
+    }
+}
+
+impl RaceModel for Model {
+    fn distance(&self, hold_time: u64, race_time: u64) -> u128 {
+        match self {
+            Self::ConstantAcceleration(model) => model.distance(hold_time, race_time),
+            Self::SpeedCapped(model) => model.distance(hold_time, race_time),
+            Self::Friction(model) => model.distance(hold_time, race_time),
+        }
+    }
+
+    fn winning_hold_times(&self, race_time: u64, record: u64) -> Option<[u64; 2]> {
+        match self {
+            Self::ConstantAcceleration(model) => model.winning_hold_times(race_time, record),
+            Self::SpeedCapped(model) => model.winning_hold_times(race_time, record),
+            Self::Friction(model) => model.winning_hold_times(race_time, record),
+        }
+    }
+}
+
+/// Parses `constant:<rate>`, `speed-capped:<max_speed>` or
+/// `friction:<deceleration>`. The last two accelerate at
+/// [`ACCELERATION_RATE`]; a bare `constant` does too.
+impl FromStr for Model {
+    type Err = InvalidModel;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidModel(s.to_string());
+        let (name, parameter) = match s.split_once(':') {
+            Some((name, parameter)) => (name, Some(parameter.parse().map_err(|_| invalid())?)),
+            None => (s, None),
+        };
+
+        match (name, parameter) {
+            ("constant", rate) => Ok(Self::ConstantAcceleration(ConstantAcceleration {
+                rate: rate.unwrap_or(ACCELERATION_RATE),
+            })),
+            ("speed-capped", Some(max_speed)) => Ok(Self::SpeedCapped(SpeedCapped {
+                rate: ACCELERATION_RATE,
+                max_speed,
+            })),
+            ("friction", Some(deceleration)) => Ok(Self::Friction(Friction {
+                rate: ACCELERATION_RATE,
+                deceleration,
+            })),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// The `--model` argument that couldn't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidModel(pub String);
+
+/// Finds the peak by binary searching where the distance stops growing, then
+/// binary searches each side of it for the outermost winning hold times.
+fn solve_numerically<M: RaceModel + ?Sized>(
+    model: &M,
+    race_time: u64,
+    record: u64,
+) -> Option<[u64; 2]> {
+    let record = record as u128;
+    let distance = |hold: u64| model.distance(hold, race_time);
+
+    let (mut lo, mut hi) = (0, race_time);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if distance(mid) < distance(mid + 1) {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    let peak = lo;
+
+    if distance(peak) <= record {
+        return None;
+    }
+
+    let (mut lo, mut hi) = (0, peak);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if distance(mid) > record {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+    let shortest = lo;
+
+    let (mut lo, mut hi) = (peak, race_time);
+    while lo < hi {
+        let mid = lo + (hi - lo).div_ceil(2);
+        if distance(mid) > record {
+            lo = mid;
+        } else {
+            hi = mid - 1;
+        }
+    }
+    let longest = lo;
+
+    Some([shortest, longest])
+}
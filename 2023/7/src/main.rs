@@ -1,20 +1,38 @@
-use std::{str::FromStr, collections::HashMap, num::ParseIntError, cmp::Ordering};
-
-use strum::EnumString;
+use std::{str::FromStr, collections::HashMap, num::ParseIntError, fmt};
 
 fn main() -> Result<(), LineParseError> {
     const INPUT: &str = include_str!("../input.txt");
-    let result = get_total_winnings_part_2(INPUT)?;
-    println!("Result: {result}");
+    let explain = std::env::args().any(|arg| arg == "--explain");
+
+    for (part, ruleset) in [(1, Ruleset::standard()), (2, Ruleset::jokers_wild())] {
+        if explain {
+            for ranked_hand in rank_hands(INPUT, &ruleset)? {
+                println!("{ranked_hand}");
+            }
+        }
+        let result = get_total_winnings(INPUT, &ruleset)?;
+        println!("Part {part}: {result}");
+    }
+
     Ok(())
 }
 
-fn get_total_winnings_part_2(input: &str) -> Result<u64, LineParseError> {
+fn get_total_winnings(input: &str, ruleset: &Ruleset) -> Result<u64, LineParseError> {
+    Ok(rank_hands(input, ruleset)?
+        .into_iter()
+        .map(|ranked_hand| ranked_hand.winnings)
+        .sum()
+    )
+}
+
+/// Sorts every hand in the input from weakest to strongest, along with what
+/// each one contributes to the total winnings.
+fn rank_hands(input: &str, ruleset: &Ruleset) -> Result<Vec<RankedHand>, LineParseError> {
     let mut hands_and_bids: Vec<(Hand, u64)> = input
         .trim()
         .lines()
         .filter(|line| !line.is_empty())
-        .map(parse_hand_and_bids)
+        .map(|line| parse_hand_and_bids(line, ruleset))
         .collect::<Result<_, _>>()?;
 
     hands_and_bids.sort();
@@ -22,83 +40,267 @@ fn get_total_winnings_part_2(input: &str) -> Result<u64, LineParseError> {
     Ok(hands_and_bids
         .into_iter()
         .enumerate()
-        .map(|(idx, (_, bid))| (idx as u64 + 1) * bid)
-        .sum()
+        .map(|(idx, (hand, bid))| {
+            let rank = idx as u64 + 1;
+            RankedHand { rank, hand, bid, winnings: rank * bid }
+        })
+        .collect()
     )
 }
 
-fn parse_hand_and_bids(line: &str) -> Result<(Hand, u64), LineParseError> {
-    let (hand_part, bid_part) = line.trim().split_once(' ').unwrap();
+#[derive(Debug)]
+struct RankedHand {
+    rank: u64,
+    hand: Hand,
+    bid: u64,
+    winnings: u64,
+}
+
+impl fmt::Display for RankedHand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cards: String = self.hand.cards.iter().map(Card::to_string).collect();
+        let substituted: String = self.hand.substituted.iter().map(Card::to_string).collect();
+        write!(
+            f,
+            "#{:<5} {cards} -> {substituted} ({}) bid {} wins {}",
+            self.rank, self.hand.hand_type, self.bid, self.winnings,
+        )
+    }
+}
+
+fn parse_hand_and_bids(line: &str, ruleset: &Ruleset) -> Result<(Hand, u64), LineParseError> {
+    let (hand_part, bid_part) = line
+        .trim()
+        .split_once(' ')
+        .ok_or(LineParseError::MissingBid)?;
     Ok((
-        hand_part.trim().parse().map_err(LineParseError::Hand)?,
+        Hand::parse(hand_part.trim(), ruleset).map_err(LineParseError::Hand)?,
         bid_part.trim().parse().map_err(LineParseError::Bid)?,
     ))
 }
 
 #[derive(Debug)]
+#[allow(dead_code)]
 enum LineParseError {
+    MissingBid,
     Hand(UnparseableHand),
     Bid(ParseIntError),
 }
 
-#[repr(u8)]
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, EnumString, Hash, Clone, Copy)]
-#[strum(ascii_case_insensitive)]
-enum Card {
-    J,
-    #[strum(serialize = "2")]
-    Two,
-    #[strum(serialize = "3")]
-    Three,
-    #[strum(serialize = "4")]
-    Four,
-    #[strum(serialize = "5")]
-    Five,
-    #[strum(serialize = "6")]
-    Six,
-    #[strum(serialize = "7")]
-    Seven,
-    #[strum(serialize = "8")]
-    Eight,
-    #[strum(serialize = "9")]
-    Nine,
-    T,
-    Q,
-    K,
-    A,
-}
-
-impl Card {
-    fn is_joker(&self) -> bool {
-        matches!(self, Card::J)
-    }
-}
-
-#[repr(u8)]
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
-enum HandType {
-    HighCard,
-    OnePair,
-    TwoPair,
-    ThreeOfAKind,
-    FullHouse,
-    FourOfAKind,
-    FiveOfAKind,
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+struct Card(char);
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Decides which cards exist, how they rank against each other, how many of
+/// them make a hand and which card, if any, stands in for whatever makes the
+/// strongest hand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Ruleset {
+    /// Every card in the deck exactly once, from weakest to strongest.
+    card_order: Vec<Card>,
+    wild_card: Option<Card>,
+    /// `None` accepts hands of any non-zero length.
+    hand_size: Option<usize>,
+}
+
+impl Ruleset {
+    const STANDARD_DECK: &'static str = "23456789TJQKA";
+    const JOKERS_DECK: &'static str = "J23456789TQKA";
+    const STANDARD_HAND_SIZE: usize = 5;
+
+    /// Part 1: `J` is a Jack and there are no wildcards.
+    fn standard() -> Self {
+        Self::new(Self::STANDARD_DECK, None)
+            .expect("the standard deck is valid")
+            .with_hand_size(Some(Self::STANDARD_HAND_SIZE))
+    }
+
+    /// Part 2: `J` is a Joker, the weakest card, and acts as a wildcard.
+    fn jokers_wild() -> Self {
+        Self::new(Self::JOKERS_DECK, Some('J'))
+            .expect("the jokers deck is valid")
+            .with_hand_size(Some(Self::STANDARD_HAND_SIZE))
+    }
+
+    /// Builds a ruleset from a deck definition such as `"J23456789TQKA"`
+    /// (weakest first) and an optional wild card. Hands of any length are
+    /// accepted until [`Ruleset::with_hand_size`] says otherwise.
+    fn new(card_order: &str, wild_card: Option<char>) -> Result<Self, InvalidRuleset> {
+        let mut cards: Vec<Card> = Vec::new();
+
+        for c in card_order.chars() {
+            if c.is_whitespace() {
+                return Err(InvalidRuleset::WhitespaceCard);
+            }
+            if cards.contains(&Card(c)) {
+                return Err(InvalidRuleset::DuplicateCard(Card(c)));
+            }
+            cards.push(Card(c));
+        }
+
+        if cards.is_empty() {
+            return Err(InvalidRuleset::EmptyDeck);
+        }
+
+        let wild_card = wild_card.map(Card);
+        if let Some(wild_card) = wild_card {
+            if !cards.contains(&wild_card) {
+                return Err(InvalidRuleset::WildCardNotInDeck(wild_card));
+            }
+        }
+
+        Ok(Self {
+            card_order: cards,
+            wild_card,
+            hand_size: None,
+        })
+    }
+
+    fn with_hand_size(mut self, hand_size: Option<usize>) -> Self {
+        self.hand_size = hand_size;
+        self
+    }
+
+    fn strength(&self, card: Card) -> Option<usize> {
+        self.card_order.iter().position(|&c| c == card)
+    }
+
+    fn is_wild(&self, card: Card) -> bool {
+        self.wild_card == Some(card)
+    }
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+#[derive(Debug)]
+#[allow(dead_code)]
+enum InvalidRuleset {
+    EmptyDeck,
+    WhitespaceCard,
+    DuplicateCard(Card),
+    WildCardNotInDeck(Card),
+}
+
+/// The shape of a hand: how many copies of each distinct card it holds, from
+/// the largest group down, e.g. `[3, 2]` for a full house or `[3, 3]` for
+/// double triples. Comparing shapes element by element ranks every hand size
+/// the way the puzzle ranks five-card hands.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+struct HandType {
+    shape: Vec<usize>,
+}
+
+#[cfg(test)]
+impl HandType {
+    fn from_shape(shape: &[usize]) -> Self {
+        let mut shape = shape.to_vec();
+        shape.sort_unstable_by(|a, b| b.cmp(a));
+        Self { shape }
+    }
+
+    fn five_of_a_kind() -> Self {
+        Self::from_shape(&[5])
+    }
+
+    fn four_of_a_kind() -> Self {
+        Self::from_shape(&[4, 1])
+    }
+
+    fn full_house() -> Self {
+        Self::from_shape(&[3, 2])
+    }
+
+    fn three_of_a_kind() -> Self {
+        Self::from_shape(&[3, 1, 1])
+    }
+
+    fn two_pair() -> Self {
+        Self::from_shape(&[2, 2, 1])
+    }
+
+    fn one_pair() -> Self {
+        Self::from_shape(&[2, 1, 1, 1])
+    }
+
+    fn high_card() -> Self {
+        Self::from_shape(&[1, 1, 1, 1, 1])
+    }
 }
 
+impl fmt::Display for HandType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self.shape.as_slice() {
+            [5] => "five of a kind",
+            [4, 1] => "four of a kind",
+            [3, 2] => "full house",
+            [3, 1, 1] => "three of a kind",
+            [2, 2, 1] => "two pair",
+            [2, 1, 1, 1] => "one pair",
+            [1, 1, 1, 1, 1] => "high card",
+            [3, 3] => "double triples",
+            _ => {
+                let groups: Vec<String> = self.shape.iter().map(usize::to_string).collect();
+                return write!(f, "{}", groups.join("-"));
+            }
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Hands order by type first and then card by card, using the strengths of
+/// the ruleset they were built with. Only compare hands built with the same
+/// ruleset.
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
 struct Hand {
     hand_type: HandType,
-    cards: [Card; 5],
+    strengths: Vec<usize>,
+    cards: Vec<Card>,
+    /// `cards` with every wild card replaced by what it ended up standing for.
+    substituted: Vec<Card>,
 }
 
 impl Hand {
-    fn new(cards: [Card; 5]) -> Self {
-        let hand_type = HandType::from(&cards);
-        Self {
-            cards,
-            hand_type
+    fn parse(s: &str, ruleset: &Ruleset) -> Result<Self, UnparseableHand> {
+        let (cards, strengths): (Vec<Card>, Vec<usize>) = s
+            .chars()
+            .map(|c| {
+                let card = Card(c);
+                ruleset
+                    .strength(card)
+                    .map(|strength| (card, strength))
+                    .ok_or(UnparseableHand::CharNotACard(c))
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .unzip();
+
+        if cards.is_empty() {
+            return Err(UnparseableHand::Empty);
+        }
+
+        if let Some(expected) = ruleset.hand_size {
+            if cards.len() != expected {
+                return Err(UnparseableHand::WrongSize { expected, found: cards.len() });
+            }
         }
+
+        let (hand_type, substituted) = HandType::classify_with_substitution(&cards, ruleset);
+
+        Ok(Self {
+            hand_type,
+            strengths,
+            cards,
+            substituted,
+        })
     }
 }
 
@@ -106,64 +308,72 @@ impl FromStr for Hand {
     type Err = UnparseableHand;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut tmp = [0_u8; 4];
-
-        let cards: [Card; 5] = s
-            .chars()
-            .map(|c| {
-                Card::from_str(c.encode_utf8(&mut tmp))
-            })
-            .collect::<Result<Vec<Card>, _>>()
-            .map_err(UnparseableHand::CharNotACard)?
-            .try_into()
-            .map_err(UnparseableHand::NotFiveElements)?;
-
-        Ok(Self::new(cards))
+        Self::parse(s, &Ruleset::default())
     }
 }
 
 #[derive(Debug)]
+#[allow(dead_code)]
 enum UnparseableHand {
-    CharNotACard(strum::ParseError),
-    NotFiveElements(Vec<Card>),
+    CharNotACard(char),
+    Empty,
+    WrongSize { expected: usize, found: usize },
 }
 
-impl From<&[Card; 5]> for HandType {
-    fn from(value: &[Card; 5]) -> Self {
+impl HandType {
+    #[cfg(test)]
+    fn classify(cards: &[Card], ruleset: &Ruleset) -> Self {
+        Self::classify_with_substitution(cards, ruleset).0
+    }
+
+    /// Classifies `cards` and returns the concrete hand the wild cards were
+    /// turned into to reach that type. Ties between equally large groups go to
+    /// the strongest card, and a hand made only of wild cards becomes the
+    /// strongest card of the deck.
+    fn classify_with_substitution(cards: &[Card], ruleset: &Ruleset) -> (Self, Vec<Card>) {
 
         let mut card_counts = HashMap::new();
+        let mut wildcards_count = 0;
 
-        for card in value {
-            *card_counts.entry(card).or_insert(0) += 1;
+        for card in cards {
+            if ruleset.is_wild(*card) {
+                wildcards_count += 1;
+            } else {
+                *card_counts.entry(card).or_insert(0) += 1;
+            }
         }
 
-        let jokers_count = card_counts.get(&Card::J).copied().unwrap_or_default();
+        let substitute = card_counts
+            .iter()
+            .max_by_key(|(card, count)| (**count, ruleset.strength(***card)))
+            .map(|(card, _)| **card)
+            .or_else(|| {
+                ruleset
+                    .card_order
+                    .iter()
+                    .rev()
+                    .find(|card| !ruleset.is_wild(**card))
+                    .copied()
+            });
 
-        let (card_with_highest_count, highest_count) = card_counts
-                .iter()
-                .filter(|(card, _)| !card.is_joker())
-                .max_by_key(|(_, count)| *count)
-                .map(|(card, count)| (*card, *count))
-                .unwrap_or((&Card::J, 0));
+        let mut shape: Vec<usize> = card_counts.into_values().collect();
+        shape.sort_unstable_by(|a, b| b.cmp(a));
 
-        let second_highest_count = card_counts
-            .into_iter()
-            .filter(|(card, _)| {
-                !card.is_joker() && *card != card_with_highest_count
-            })
-            .map(|(_, count)| count)
-            .max()
-            .unwrap_or_default();
-
-        match (highest_count + jokers_count, second_highest_count) {
-            (5, _) => Self::FiveOfAKind,
-            (4, _) => Self::FourOfAKind,
-            (3, 2) => Self::FullHouse,
-            (3, _) => Self::ThreeOfAKind,
-            (2, 2) => Self::TwoPair,
-            (2, _) => Self::OnePair,
-            _ => Self::HighCard,
+        // Wildcards are always best spent joining the largest group.
+        match shape.first_mut() {
+            Some(highest_count) => *highest_count += wildcards_count,
+            None => shape.push(wildcards_count),
         }
+
+        let substituted = cards
+            .iter()
+            .map(|&card| match substitute {
+                Some(substitute) if ruleset.is_wild(card) => substitute,
+                _ => card,
+            })
+            .collect();
+
+        (Self { shape }, substituted)
     }
 }
 
@@ -171,7 +381,7 @@ impl From<&[Card; 5]> for HandType {
 mod tests {
     use std::str::FromStr;
 
-    use crate::{get_total_winnings_part_2, HandType, Hand};
+    use crate::{get_total_winnings, rank_hands, HandType, Hand, Ruleset};
     use test_case::test_case;
 
     const EXAMPLE_INPUT: &str = r"
@@ -182,30 +392,156 @@ mod tests {
         QQQJA 483
     ";
 
+    #[test]
+    fn example_case_part_1() {
+        const EXPECTED_OUTPUT: u64 = 6440;
+        let result = get_total_winnings(EXAMPLE_INPUT, &Ruleset::standard());
+        assert_eq!(result.unwrap(), EXPECTED_OUTPUT);
+    }
+
     #[test]
     fn example_case_part_2() {
         const EXPECTED_OUTPUT: u64 = 5905;
-        let result = get_total_winnings_part_2(EXAMPLE_INPUT);
+        let result = get_total_winnings(EXAMPLE_INPUT, &Ruleset::jokers_wild());
         assert_eq!(result.unwrap(), EXPECTED_OUTPUT);
     }
 
-    #[test_case("4558J", HandType::ThreeOfAKind)]
-    #[test_case("T7JJT", HandType::FourOfAKind)]
-    #[test_case("AAJJJ", HandType::FiveOfAKind)]
-    #[test_case("9J2TT", HandType::ThreeOfAKind)]
-    #[test_case("T8JTJ", HandType::FourOfAKind)]
-    #[test_case("6J69J", HandType::FourOfAKind)]
-    #[test_case("4J935", HandType::OnePair)]
-    #[test_case("JJJ8J", HandType::FiveOfAKind)]
-    #[test_case("222J2", HandType::FiveOfAKind)]
-    #[test_case("JKKKJ", HandType::FiveOfAKind)]
-    #[test_case("QJ533", HandType::ThreeOfAKind)]
-    #[test_case("666JJ", HandType::FiveOfAKind)]
-    #[test_case("AA9J7", HandType::ThreeOfAKind)]
-    #[test_case("QJ777", HandType::FourOfAKind)]
-    #[test_case("JJJJJ", HandType::FiveOfAKind)]
-    fn test_hand_type_with_jokers(line: &str, expected_type: HandType) {
+    #[test_case("4558J", "45585")]
+    #[test_case("T7JJT", "T7TTT")]
+    #[test_case("AAJJJ", "AAAAA")]
+    #[test_case("KKJQQ", "KKKQQ")]
+    #[test_case("JJJJJ", "AAAAA")]
+    #[test_case("23J45", "23545")]
+    fn test_joker_substitution(line: &str, expected: &str) {
+        let hand = Hand::parse(line, &Ruleset::jokers_wild()).unwrap();
+        let substituted: String = hand.substituted.iter().map(|card| card.0).collect();
+        assert_eq!(substituted, expected);
+        assert_eq!(hand.hand_type, HandType::classify(&hand.substituted, &Ruleset::standard()));
+    }
+
+    #[test]
+    fn test_ranking_report() {
+        let ranked = rank_hands(EXAMPLE_INPUT, &Ruleset::jokers_wild()).unwrap();
+        let summary: Vec<(u64, String, u64, u64)> = ranked
+            .iter()
+            .map(|r| (r.rank, r.hand.cards.iter().map(|c| c.0).collect(), r.bid, r.winnings))
+            .collect();
+
+        assert_eq!(summary, vec![
+            (1, "32T3K".to_string(), 765, 765),
+            (2, "KK677".to_string(), 28, 56),
+            (3, "T55J5".to_string(), 684, 2052),
+            (4, "QQQJA".to_string(), 483, 1932),
+            (5, "KTJJT".to_string(), 220, 1100),
+        ]);
+        assert_eq!(ranked[4].to_string(), "#5     KTJJT -> KTTTT (four of a kind) bid 220 wins 1100");
+    }
+
+    #[test_case("4558J", HandType::one_pair())]
+    #[test_case("T7JJT", HandType::two_pair())]
+    #[test_case("AAJJJ", HandType::full_house())]
+    #[test_case("JJJJJ", HandType::five_of_a_kind())]
+    fn test_hand_type_without_jokers(line: &str, expected_type: HandType) {
         let hand = Hand::from_str(line).unwrap();
         assert_eq!(hand.hand_type, expected_type);
     }
+
+    #[test]
+    fn test_custom_wild_card() {
+        let ruleset = Ruleset::new("23456789TJQKA", Some('2')).unwrap();
+        let hand = Hand::parse("22KQ3", &ruleset).unwrap();
+        assert_eq!(hand.hand_type, HandType::three_of_a_kind());
+
+        let weaker = Hand::parse("3AT3K", &ruleset).unwrap();
+        let stronger = Hand::parse("32T3K", &ruleset).unwrap();
+        assert_eq!(stronger.hand_type, HandType::three_of_a_kind());
+        assert!(weaker < stronger);
+    }
+
+    #[test]
+    fn test_jack_strength_depends_on_ruleset() {
+        let jack = Hand::parse("JKKK2", &Ruleset::standard()).unwrap();
+        let queen = Hand::parse("QQQQ2", &Ruleset::standard()).unwrap();
+        assert!(jack < queen);
+
+        let joker = Hand::parse("JKKK2", &Ruleset::jokers_wild()).unwrap();
+        let two = Hand::parse("2KKK2", &Ruleset::jokers_wild()).unwrap();
+        assert!(joker > two);
+        assert_eq!(joker.hand_type, HandType::four_of_a_kind());
+    }
+
+    #[test]
+    fn test_invalid_ruleset() {
+        assert!(Ruleset::new("", None).is_err());
+        assert!(Ruleset::new("23456789TJQKK", None).is_err());
+        assert!(Ruleset::new("2345 6789", None).is_err());
+        assert!(Ruleset::new("23456789TJQKA", Some('X')).is_err());
+    }
+
+    #[test]
+    fn test_standard_hand_size_is_enforced() {
+        assert!(Hand::from_str("2345").is_err());
+        assert!(Hand::from_str("234567").is_err());
+        assert!(Hand::from_str("2345X").is_err());
+    }
+
+    #[test_case("AAA222", &[3, 3], "double triples")]
+    #[test_case("AAAAAA", &[6], "6")]
+    #[test_case("A2", &[1, 1], "1-1")]
+    #[test_case("7777777", &[7], "7")]
+    #[test_case("AA22334", &[2, 2, 2, 1], "2-2-2-1")]
+    fn test_custom_hand_sizes(line: &str, expected_shape: &[usize], expected_name: &str) {
+        let ruleset = Ruleset::new("23456789TJQKA", None).unwrap();
+        let hand = Hand::parse(line, &ruleset).unwrap();
+        assert_eq!(hand.hand_type, HandType::from_shape(expected_shape));
+        assert_eq!(hand.hand_type.to_string(), expected_name);
+    }
+
+    #[test]
+    fn test_custom_deck() {
+        let ruleset = Ruleset::new("abcdef", Some('f')).unwrap().with_hand_size(Some(6));
+        let double_triples = Hand::parse("aaabbb", &ruleset).unwrap();
+        let four_of_a_kind = Hand::parse("aaaffe", &ruleset).unwrap();
+        let full_house = Hand::parse("ccccbb", &ruleset).unwrap();
+
+        assert_eq!(double_triples.hand_type, HandType::from_shape(&[3, 3]));
+        assert!(double_triples < full_house);
+        assert!(full_house < four_of_a_kind);
+        assert!(Hand::parse("aaabbA", &ruleset).is_err());
+        assert!(Hand::parse("aaabb", &ruleset).is_err());
+    }
+
+    #[test]
+    fn test_multiset_shapes_rank_like_named_types() {
+        let ranked = [
+            HandType::high_card(),
+            HandType::one_pair(),
+            HandType::two_pair(),
+            HandType::three_of_a_kind(),
+            HandType::full_house(),
+            HandType::four_of_a_kind(),
+            HandType::five_of_a_kind(),
+        ];
+        assert!(ranked.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test_case("4558J", HandType::three_of_a_kind())]
+    #[test_case("T7JJT", HandType::four_of_a_kind())]
+    #[test_case("AAJJJ", HandType::five_of_a_kind())]
+    #[test_case("9J2TT", HandType::three_of_a_kind())]
+    #[test_case("T8JTJ", HandType::four_of_a_kind())]
+    #[test_case("6J69J", HandType::four_of_a_kind())]
+    #[test_case("4J935", HandType::one_pair())]
+    #[test_case("JJJ8J", HandType::five_of_a_kind())]
+    #[test_case("222J2", HandType::five_of_a_kind())]
+    #[test_case("JKKKJ", HandType::five_of_a_kind())]
+    #[test_case("QJ533", HandType::three_of_a_kind())]
+    #[test_case("666JJ", HandType::five_of_a_kind())]
+    #[test_case("AA9J7", HandType::three_of_a_kind())]
+    #[test_case("QJ777", HandType::four_of_a_kind())]
+    #[test_case("JJJJJ", HandType::five_of_a_kind())]
+    fn test_hand_type_with_jokers(line: &str, expected_type: HandType) {
+        let hand = Hand::parse(line, &Ruleset::jokers_wild()).unwrap();
+        assert_eq!(hand.hand_type, expected_type);
+    }
 }
\ No newline at end of file
@@ -1,74 +1,213 @@
 #![feature(iter_map_windows)]
-#![feature(iterator_try_reduce)]
 
 use std::{str::FromStr, num::ParseIntError};
 
-fn main() -> Result<(), SequenceParseError> {
+fn main() -> Result<(), SequenceError> {
     const INPUT: &str = include_str!("../input.txt");
-    let (past, future) = sum_predictions(INPUT)?;
+
+    let args: Vec<String> = std::env::args().collect();
+    let steps = args
+        .iter()
+        .skip_while(|arg| *arg != "--steps")
+        .nth(1)
+        .map(|steps| steps.parse().unwrap_or_else(|error| {
+            eprintln!("Invalid --steps `{steps}`: {error}");
+            std::process::exit(2);
+        }))
+        .unwrap_or(1);
+
+    if args.iter().any(|arg| arg == "--explain") {
+        for line in INPUT.lines().filter(|line| !line.trim().is_empty()) {
+            let extrapolation = extrapolate_sequence(line, steps)?;
+            println!(
+                "degree {}: past {:?} | future {:?}",
+                extrapolation.degree, extrapolation.past, extrapolation.future,
+            );
+        }
+    }
+
+    let (past, future) = sum_predictions(INPUT, steps)?;
     println!("Past: {past} | Future: {future}");
     Ok(())
 }
 
-fn sum_predictions(input: &str) -> Result<(i64, i64), SequenceParseError> {
+/// Sums, over every sequence, the values `steps` positions before its start
+/// and after its end.
+fn sum_predictions(input: &str, steps: usize) -> Result<(i64, i64), SequenceError> {
     input
         .trim()
         .lines()
         .filter(|line| !line.is_empty())
-        .map(predict_sequence)
-        .try_fold((0, 0), |(acc_past, acc_future), val| {
+        .map(|line| predict_sequence(line, steps))
+        .try_fold((0_i64, 0_i64), |(acc_past, acc_future), val| {
             let (past, future) = val?;
-            Ok((acc_past + past, acc_future + future))
+            Ok((
+                acc_past.checked_add(past).ok_or(SequenceError::Overflow)?,
+                acc_future.checked_add(future).ok_or(SequenceError::Overflow)?,
+            ))
         })
 }
 
-fn predict_sequence(line: &str) -> Result<(i64, i64), SequenceParseError> {
-    let mut values: Vec<i64> = line
+/// The values `steps` positions before the start and after the end of the
+/// sequence, jumping straight there with [`DifferenceTable::value_at`].
+fn predict_sequence(line: &str, steps: usize) -> Result<(i64, i64), SequenceError> {
+    let values = parse_sequence(line)?;
+    let last = values.len() as i64 - 1;
+    let steps = i64::try_from(steps).map_err(|_| SequenceError::Overflow)?;
+
+    let table = DifferenceTable::new(values)?;
+    let future = last.checked_add(steps).ok_or(SequenceError::Overflow)?;
+
+    Ok((table.value_at(-steps)?, table.value_at(future)?))
+}
+
+fn parse_sequence(line: &str) -> Result<Vec<i64>, SequenceError> {
+    line
         .split_whitespace()
         .map(FromStr::from_str)
         .collect::<Result<_, _>>()
-        .map_err(SequenceParseError::InvalidValue)?;
-
-    let mut all_zeroes = false;
-    let mut last_elements = Vec::with_capacity(values.len() - 1);
-    let mut first_elements = Vec::with_capacity(values.len() - 1);
-
-    while !all_zeroes {
-        all_zeroes = true;
-        
-        first_elements.push(values.first().copied().unwrap_or_default());
-        last_elements.push(values.last().copied().unwrap_or_default());
-
-        values = values
-            .into_iter()
-            .map_windows(|[a, b]| b - a)
-            .inspect(|sub| all_zeroes &= *sub == 0)
-            .collect();
+        .map_err(SequenceError::InvalidValue)
+}
+
+/// `steps` values on each side of a sequence, nearest first, along with the
+/// degree of the polynomial behind it.
+#[derive(Debug, PartialEq, Eq)]
+struct Extrapolation {
+    degree: usize,
+    past: Vec<i64>,
+    future: Vec<i64>,
+}
+
+fn extrapolate_sequence(line: &str, steps: usize) -> Result<Extrapolation, SequenceError> {
+    let table = DifferenceTable::new(parse_sequence(line)?)?;
+
+    Ok(Extrapolation {
+        degree: table.degree(),
+        past: table.backward(steps)?,
+        future: table.forward(steps)?,
+    })
+}
+
+/// The edges of a sequence's difference pyramid: the first and last element of
+/// the sequence, of its differences, of their differences, and so on down to
+/// the first row that is constant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DifferenceTable {
+    first_elements: Vec<i64>,
+    last_elements: Vec<i64>,
+}
+
+impl DifferenceTable {
+    /// Fails with [`SequenceError::NotPolynomial`] if the differences run out
+    /// before a row of zeroes shows up, which also covers sequences too short to
+    /// have any differences at all.
+    fn new(mut values: Vec<i64>) -> Result<Self, SequenceError> {
+        if values.is_empty() {
+            return Err(SequenceError::Empty);
+        }
+
+        let mut all_zeroes = false;
+        let mut last_elements = Vec::with_capacity(values.len());
+        let mut first_elements = Vec::with_capacity(values.len());
+
+        while !all_zeroes {
+            let (Some(&first), Some(&last)) = (values.first(), values.last()) else {
+                return Err(SequenceError::NotPolynomial);
+            };
+
+            first_elements.push(first);
+            last_elements.push(last);
+
+            values = values
+                .into_iter()
+                .map_windows(|[a, b]| b.checked_sub(*a))
+                .collect::<Option<_>>()
+                .ok_or(SequenceError::Overflow)?;
+
+            all_zeroes = !values.is_empty() && values.iter().all(|sub| *sub == 0);
+        }
+
+        Ok(Self {
+            first_elements,
+            last_elements,
+        })
+    }
+
+    /// The degree of the polynomial generating the sequence, which is how many
+    /// times it has to be differentiated before it becomes constant.
+    fn degree(&self) -> usize {
+        self.first_elements.len() - 1
     }
 
-    let past = first_elements
-        .into_iter()
-        .rev()
-        .reduce(|a, b| b - a)
-        .ok_or_else(|| unreachable!("It is guaranteed to have been populated with elements"))?;
+    /// The next `steps` values after the end of the sequence.
+    fn forward(&self, steps: usize) -> Result<Vec<i64>, SequenceError> {
+        let mut edges = self.last_elements.clone();
 
-    let future = last_elements
-        .into_iter()
-        .sum();
+        (0..steps)
+            .map(|_| {
+                for row in (0..self.degree()).rev() {
+                    edges[row] = edges[row]
+                        .checked_add(edges[row + 1])
+                        .ok_or(SequenceError::Overflow)?;
+                }
+                Ok(edges[0])
+            })
+            .collect()
+    }
+
+    /// The `steps` values before the start of the sequence, nearest first.
+    fn backward(&self, steps: usize) -> Result<Vec<i64>, SequenceError> {
+        let mut edges = self.first_elements.clone();
+
+        (0..steps)
+            .map(|_| {
+                for row in (0..self.degree()).rev() {
+                    edges[row] = edges[row]
+                        .checked_sub(edges[row + 1])
+                        .ok_or(SequenceError::Overflow)?;
+                }
+                Ok(edges[0])
+            })
+            .collect()
+    }
 
-    Ok((past, future))
+    /// The value at any position, `0` being the first element of the sequence,
+    /// using Newton's forward difference formula instead of walking there.
+    /// Intermediate terms get the room of an `i128`, so only results that
+    /// don't fit an `i64` overflow.
+    fn value_at(&self, index: i64) -> Result<i64, SequenceError> {
+        let mut binomial: i128 = 1;
+        let mut value: i128 = 0;
 
+        for (i, &difference) in self.first_elements.iter().enumerate() {
+            value = binomial
+                .checked_mul(difference as i128)
+                .and_then(|term| value.checked_add(term))
+                .ok_or(SequenceError::Overflow)?;
+
+            // C(index, i + 1) from C(index, i); the division is always exact.
+            binomial = binomial
+                .checked_mul(index as i128 - i as i128)
+                .ok_or(SequenceError::Overflow)?
+                / (i as i128 + 1);
+        }
+
+        i64::try_from(value).map_err(|_| SequenceError::Overflow)
+    }
 }
 
-#[derive(Debug)]
-enum SequenceParseError {
+#[derive(Debug, PartialEq, Eq)]
+#[allow(dead_code)]
+enum SequenceError {
     InvalidValue(ParseIntError),
+    Empty,
+    NotPolynomial,
+    Overflow,
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::sum_predictions;
-
+    use crate::{sum_predictions, extrapolate_sequence, parse_sequence, predict_sequence, DifferenceTable, Extrapolation, SequenceError};
 
     const EXAMPLE_INPUT: &str = r"
         0 3 6 9 12 15
@@ -80,7 +219,95 @@ mod tests {
     fn example_case() {
         const EXPECTED_PAST: i64 = 2;
         const EXPECTED_FUTURE: i64 = 114;
-        let result = sum_predictions(EXAMPLE_INPUT).unwrap();
+        let result = sum_predictions(EXAMPLE_INPUT, 1).unwrap();
         assert_eq!(result, (EXPECTED_PAST, EXPECTED_FUTURE));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn extrapolate_many_steps() {
+        let result = extrapolate_sequence("10 13 16 21 30 45", 3).unwrap();
+        assert_eq!(result, Extrapolation {
+            degree: 3,
+            past: vec![5, -4, -19],
+            future: vec![68, 101, 146],
+        });
+    }
+
+    #[test]
+    fn predictions_at_any_horizon() {
+        for steps in [1, 3, 20] {
+            let walked = EXAMPLE_INPUT
+                .trim()
+                .lines()
+                .map(|line| extrapolate_sequence(line, steps).unwrap())
+                .fold((0, 0), |(past, future), extrapolation| (
+                    past + extrapolation.past[steps - 1],
+                    future + extrapolation.future[steps - 1],
+                ));
+
+            assert_eq!(sum_predictions(EXAMPLE_INPUT, steps), Ok(walked));
+        }
+
+        assert_eq!(sum_predictions(EXAMPLE_INPUT, 0), Ok((11, 81)));
+        assert_eq!(predict_sequence("0 3 6", 1_000_000_000_000), Ok((-3_000_000_000_000, 3_000_000_000_006)));
+        assert_eq!(predict_sequence("0 3 6", usize::MAX), Err(SequenceError::Overflow));
+    }
+
+    #[test]
+    fn detect_degrees() {
+        let degree = |line| extrapolate_sequence(line, 0).unwrap().degree;
+        assert_eq!(degree("7 7 7 7"), 0);
+        assert_eq!(degree("0 3 6 9 12 15"), 1);
+        assert_eq!(degree("1 3 6 10 15 21"), 2);
+        assert_eq!(degree("1 8 27 64 125"), 3);
+    }
+
+    #[test]
+    fn closed_form_matches_table() {
+        for line in EXAMPLE_INPUT.trim().lines() {
+            let values = parse_sequence(line).unwrap();
+            let table = DifferenceTable::new(values.clone()).unwrap();
+
+            let future = table.forward(20).unwrap();
+            let past = table.backward(20).unwrap();
+
+            for (idx, value) in values.iter().enumerate() {
+                assert_eq!(table.value_at(idx as i64), Ok(*value));
+            }
+            for (step, value) in future.iter().enumerate() {
+                assert_eq!(table.value_at((values.len() + step) as i64), Ok(*value));
+            }
+            for (step, value) in past.iter().enumerate() {
+                assert_eq!(table.value_at(-(step as i64) - 1), Ok(*value));
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_sequences() {
+        assert_eq!(predict_sequence("", 1), Err(SequenceError::Empty));
+        assert_eq!(predict_sequence("5", 1), Err(SequenceError::NotPolynomial));
+        assert_eq!(predict_sequence("1 2 4 8", 1), Err(SequenceError::NotPolynomial));
+        assert_eq!(predict_sequence("3 3", 1), Ok((3, 3)));
+        assert_eq!(predict_sequence("1 2", 1), Err(SequenceError::NotPolynomial));
+        assert_eq!(predict_sequence("1 2 4 8 15", 1), Ok((0, 26)));
+    }
+
+    #[test]
+    fn overflow_is_reported() {
+        let (min, max) = (i64::MIN, i64::MAX);
+        assert_eq!(predict_sequence(&format!("{min} {max} {max}"), 1), Err(SequenceError::Overflow));
+        assert_eq!(predict_sequence(&format!("{} {} {max}", max - 2, max - 1), 1), Err(SequenceError::Overflow));
+        assert_eq!(predict_sequence(&format!("{min} {} {}", min + 1, min + 2), 1), Err(SequenceError::Overflow));
+        assert_eq!(predict_sequence(&format!("{max} {max}"), 1), Ok((max, max)));
+        assert_eq!(sum_predictions(&format!("{max} {max}\n1 1"), 1), Err(SequenceError::Overflow));
+
+        let table = DifferenceTable::new(vec![0, 1, 2]).unwrap();
+        assert_eq!(table.value_at(max), Ok(max));
+        assert_eq!(table.forward(2), Ok(vec![3, 4]));
+
+        let table = DifferenceTable::new(vec![0, 1, 4, 9]).unwrap();
+        assert_eq!(table.value_at(3_037_000_499), Ok(3_037_000_499 * 3_037_000_499));
+        assert_eq!(table.value_at(3_037_000_500), Err(SequenceError::Overflow));
+    }
+}